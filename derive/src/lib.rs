@@ -0,0 +1,207 @@
+// Derives `lib::Semigroup` and `lib::Monoid` for structs, field by field:
+// mappend combines each field of the two values, and mempty is every field's
+// mempty.
+//
+//     #[derive(Clone, Semigroup, Monoid)]
+//     struct Tally {
+//         #[monoid(sum)]
+//         count: u32,
+//         #[monoid(with = "keep_first")]
+//         first: Option<char>,
+//         seen: Seen,
+//     }
+//
+// A field without an attribute has to be a Monoid itself. The attribute swaps
+// that for something else:
+//
+//     #[monoid(sum)]             adds the fields, and starts from lib::Zero
+//     #[monoid(product)]         multiplies them, and starts from lib::One
+//     #[monoid(with = "path")]   calls path::mappend and path::mempty
+//
+// The generated code refers to the traits through `crate::lib`. Outside of
+// aoc-2021, name the crate with `#[monoid(crate = "aoc_2021")]` on the struct.
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    parse_macro_input, parse_quote, Attribute, Data, DeriveInput, Error, Fields, Index, Lit, Meta,
+    NestedMeta, Path, Result,
+};
+
+enum Rule {
+    Monoid,
+    Sum,
+    Product,
+    With(Path),
+}
+
+// The arguments of every #[monoid(...)] on an item
+fn monoid_args(attrs: &[Attribute]) -> Result<Vec<NestedMeta>> {
+    let mut args: Vec<NestedMeta> = Vec::new();
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("monoid")) {
+        match attr.parse_meta()? {
+            Meta::List(list) => args.extend(list.nested),
+            meta => return Err(Error::new_spanned(meta, "expected #[monoid(...)]")),
+        }
+    }
+    Ok(args)
+}
+
+fn string_path(lit: &Lit) -> Result<Path> {
+    match lit {
+        Lit::Str(s) => s.parse(),
+        lit => Err(Error::new_spanned(lit, "expected a path in a string")),
+    }
+}
+
+fn field_rule(attrs: &[Attribute]) -> Result<Rule> {
+    let mut rule = Rule::Monoid;
+    for arg in monoid_args(attrs)? {
+        rule = match &arg {
+            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("sum") => Rule::Sum,
+            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("product") => Rule::Product,
+            NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("with") => {
+                Rule::With(string_path(&nv.lit)?)
+            }
+            _ => {
+                return Err(Error::new_spanned(
+                    arg,
+                    "expected sum, product or with = \"path\"",
+                ))
+            }
+        };
+    }
+    Ok(rule)
+}
+
+fn crate_path(attrs: &[Attribute]) -> Result<Path> {
+    let mut krate: Path = parse_quote!(crate);
+    for arg in monoid_args(attrs)? {
+        krate = match &arg {
+            NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("crate") => {
+                string_path(&nv.lit)?
+            }
+            _ => return Err(Error::new_spanned(arg, "expected crate = \"path\"")),
+        };
+    }
+    Ok(krate)
+}
+
+struct Field {
+    // The field's name, or its index in a tuple struct
+    member: TokenStream2,
+    rule: Rule,
+}
+
+fn fields(input: &DeriveInput) -> Result<(Vec<Field>, &Fields)> {
+    let data = match &input.data {
+        Data::Struct(data) => data,
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "Semigroup and Monoid can only be derived for structs",
+            ))
+        }
+    };
+    let mut fields: Vec<Field> = Vec::new();
+    for (idx, field) in data.fields.iter().enumerate() {
+        let member = match &field.ident {
+            Some(ident) => quote!(#ident),
+            None => {
+                let idx = Index::from(idx);
+                quote!(#idx)
+            }
+        };
+        fields.push(Field {
+            member,
+            rule: field_rule(&field.attrs)?,
+        });
+    }
+    Ok((fields, &data.fields))
+}
+
+// Self { a: .., b: .. }, Self(.., ..) or Self, from one expression per field
+fn construct(shape: &Fields, fields: &[Field], values: Vec<TokenStream2>) -> TokenStream2 {
+    match shape {
+        Fields::Named(_) => {
+            let members = fields.iter().map(|field| &field.member);
+            quote!(Self { #(#members: #values),* })
+        }
+        Fields::Unnamed(_) => quote!(Self(#(#values),*)),
+        Fields::Unit => quote!(Self),
+    }
+}
+
+// Every type parameter has to implement the trait too
+fn expand(input: &DeriveInput, bound: Path, body: TokenStream2) -> TokenStream2 {
+    let mut generics = input.generics.clone();
+    for param in generics.type_params_mut() {
+        param.bounds.push(parse_quote!(#bound));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let name = &input.ident;
+    quote! {
+        impl #impl_generics #bound for #name #ty_generics #where_clause {
+            #body
+        }
+    }
+}
+
+fn semigroup(input: &DeriveInput) -> Result<TokenStream2> {
+    let krate = crate_path(&input.attrs)?;
+    let (fields, shape) = fields(input)?;
+    let values = fields
+        .iter()
+        .map(|Field { member, rule }| match rule {
+            Rule::Monoid => quote!(#krate::lib::Semigroup::mappend(fst.#member, snd.#member)),
+            Rule::Sum => quote!(fst.#member + snd.#member),
+            Rule::Product => quote!(fst.#member * snd.#member),
+            Rule::With(path) => quote!(#path::mappend(fst.#member, snd.#member)),
+        })
+        .collect();
+    let value = construct(shape, &fields, values);
+    let body = quote! {
+        #[allow(unused_variables)]
+        fn mappend(fst: Self, snd: Self) -> Self {
+            #value
+        }
+    };
+    Ok(expand(input, parse_quote!(#krate::lib::Semigroup), body))
+}
+
+fn monoid(input: &DeriveInput) -> Result<TokenStream2> {
+    let krate = crate_path(&input.attrs)?;
+    let (fields, shape) = fields(input)?;
+    let values = fields
+        .iter()
+        .map(|Field { rule, .. }| match rule {
+            Rule::Monoid => quote!(#krate::lib::Monoid::mempty()),
+            Rule::Sum => quote!(#krate::lib::Zero::zero()),
+            Rule::Product => quote!(#krate::lib::One::one()),
+            Rule::With(path) => quote!(#path::mempty()),
+        })
+        .collect();
+    let value = construct(shape, &fields, values);
+    let body = quote! {
+        fn mempty() -> Self {
+            #value
+        }
+    };
+    Ok(expand(input, parse_quote!(#krate::lib::Monoid), body))
+}
+
+#[proc_macro_derive(Semigroup, attributes(monoid))]
+pub fn derive_semigroup(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    semigroup(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+#[proc_macro_derive(Monoid, attributes(monoid))]
+pub fn derive_monoid(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    monoid(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
@@ -0,0 +1,236 @@
+// Plays the trace events of a run back in the terminal, one frame per event. A
+// grid in an event is drawn as coloured cells and a list as bars, under the
+// event's message. Events without either keep showing the last picture, so a
+// board that wins is shown as it was on the draw that made it win.
+//
+// There's no way to read single key presses with std alone, so the controls are
+// read a line at a time: Enter pauses and resumes, + and - change the speed, n
+// steps a frame (and pauses) and q stops.
+use super::trace::{Event, Grid, Value};
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+pub const DEFAULT_FPS: u32 = 10;
+const MAX_FPS: u32 = 240;
+const BAR_WIDTH: i64 = 50;
+
+// Background colours from the 256 colour palette, one per label. Zero is a wall
+// or an unmarked number, and gets the dark one.
+const DARK: u8 = 236;
+const PALETTE: [u8; 12] = [39, 208, 46, 201, 226, 33, 160, 51, 129, 34, 214, 99];
+
+fn colour(value: i64) -> u8 {
+    match value {
+        0 => DARK,
+        v => PALETTE[(v - 1).rem_euclid(PALETTE.len() as i64) as usize],
+    }
+}
+
+// Cells are two columns wide so they come out about square
+fn draw_grid(grid: &Grid) -> String {
+    let rows: Vec<String> = grid
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|cell| match cell {
+                    Some(v) => format!("\x1b[48;5;{}m  \x1b[0m", colour(*v)),
+                    None => "  ".to_string(),
+                })
+                .collect()
+        })
+        .collect();
+    rows.join("\n")
+}
+
+// One bar per item, scaled to the largest
+fn draw_list(items: &[i64]) -> String {
+    let largest = items.iter().copied().max().unwrap_or(0).max(1);
+    let rows: Vec<String> = items
+        .iter()
+        .enumerate()
+        .map(|(idx, item)| {
+            let width = (*item as i128 * BAR_WIDTH as i128 / largest as i128).max(0) as usize;
+            format!(
+                "{:>3} \x1b[48;5;{}m{}\x1b[0m {}",
+                idx,
+                colour(idx as i64 + 1),
+                " ".repeat(width),
+                item
+            )
+        })
+        .collect();
+    rows.join("\n")
+}
+
+fn picture(event: &Event) -> Option<String> {
+    event.fields.iter().find_map(|(_, value)| match value {
+        Value::Grid(grid) => Some(draw_grid(grid)),
+        Value::List(items) => Some(draw_list(items)),
+        _ => None,
+    })
+}
+
+// Every event as a frame, its message first. The title goes above each one.
+pub fn frames(title: &str, events: &[Event]) -> Vec<String> {
+    let mut last = String::new();
+    events
+        .iter()
+        .map(|event| {
+            if let Some(picture) = picture(event) {
+                last = picture;
+            }
+            format!("{}: {}\n\n{}", title, event.message, last)
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Control {
+    Pause,
+    Faster,
+    Slower,
+    Step,
+    Quit,
+}
+
+fn parse_control(line: &str) -> Option<Control> {
+    match line.trim() {
+        "" | "p" => Some(Control::Pause),
+        "+" | "f" => Some(Control::Faster),
+        "-" | "s" => Some(Control::Slower),
+        "n" => Some(Control::Step),
+        "q" => Some(Control::Quit),
+        _ => None,
+    }
+}
+
+// Reads the controls off stdin on a thread of its own. When stdin isn't a
+// terminal, e.g. when the input came from it, there are no controls.
+fn listen() -> Receiver<Control> {
+    let (sender, receiver) = mpsc::channel();
+    if io::stdin().is_terminal() {
+        thread::spawn(move || {
+            for line in io::stdin().lock().lines() {
+                let Ok(line) = line else { break };
+                if let Some(control) = parse_control(&line) {
+                    if sender.send(control).is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+    }
+    receiver
+}
+
+fn status(frame: usize, frames: usize, fps: u32, paused: bool) -> String {
+    format!(
+        "Frame {}/{} at {} fps{}. Enter pauses, + and - change the speed, n steps and q quits.",
+        frame + 1,
+        frames,
+        fps,
+        if paused { " (paused)" } else { "" }
+    )
+}
+
+pub fn play(frames: &[String], fps: u32) -> io::Result<()> {
+    let controls = listen();
+    let mut out = io::stdout().lock();
+    let mut fps = fps.clamp(1, MAX_FPS);
+    let mut paused = false;
+    let mut idx = 0;
+    // Clear the screen once, after that every frame is drawn over the last one
+    write!(out, "\x1b[2J")?;
+    while idx < frames.len() {
+        let screen = format!(
+            "{}\n\n{}",
+            frames[idx],
+            status(idx, frames.len(), fps, paused)
+        );
+        // Clearing to the end of each line and of the screen wipes out what's
+        // left of a longer frame
+        write!(
+            out,
+            "\x1b[H{}\x1b[K\x1b[J",
+            screen.replace('\n', "\x1b[K\n")
+        )?;
+        out.flush()?;
+
+        let wait = Duration::from_secs(1) / fps;
+        let control = if paused {
+            // With nobody left to resume, carry on
+            controls.recv().ok().or(Some(Control::Pause))
+        } else {
+            match controls.recv_timeout(wait) {
+                Ok(control) => Some(control),
+                Err(RecvTimeoutError::Timeout) => None,
+                Err(RecvTimeoutError::Disconnected) => {
+                    thread::sleep(wait);
+                    None
+                }
+            }
+        };
+        match control {
+            None => idx += 1,
+            Some(Control::Pause) => paused = !paused,
+            Some(Control::Faster) => fps = (fps * 2).min(MAX_FPS),
+            Some(Control::Slower) => fps = (fps / 2).max(1),
+            Some(Control::Step) => {
+                paused = true;
+                idx += 1;
+            }
+            Some(Control::Quit) => break,
+        }
+    }
+    writeln!(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_without_a_picture_keep_the_last_one() {
+        let events = vec![
+            Event::new("start", "Nothing yet".to_string()),
+            Event::new("step", "One cell".to_string()).with("grid", vec![vec![Some(1), None]]),
+            Event::new("done", "Done".to_string()),
+        ];
+        let frames = frames("Day 9, Part 2", &events);
+        let cell = "\x1b[48;5;39m  \x1b[0m  ";
+        assert_eq!(
+            frames,
+            vec![
+                "Day 9, Part 2: Nothing yet\n\n".to_string(),
+                format!("Day 9, Part 2: One cell\n\n{}", cell),
+                format!("Day 9, Part 2: Done\n\n{}", cell),
+            ]
+        );
+    }
+
+    #[test]
+    fn bars_are_scaled_to_the_largest() {
+        let bars = draw_list(&[10, 0, 5]);
+        let widths: Vec<usize> = bars
+            .lines()
+            .map(|line| line.chars().filter(|c| *c == ' ').count())
+            .collect();
+        // The label's padding and the spaces around the bar, then the bar
+        assert_eq!(widths, vec![4 + 50, 4, 4 + 25]);
+        assert!(bars.lines().next().unwrap().ends_with(" 10"));
+    }
+
+    #[test]
+    fn reads_controls_a_line_at_a_time() {
+        assert_eq!(parse_control(""), Some(Control::Pause));
+        assert_eq!(parse_control(" + "), Some(Control::Faster));
+        assert_eq!(parse_control("-"), Some(Control::Slower));
+        assert_eq!(parse_control("n"), Some(Control::Step));
+        assert_eq!(parse_control("q"), Some(Control::Quit));
+        assert_eq!(parse_control("what"), None);
+        assert_eq!(colour(0), DARK);
+        assert_eq!(colour(13), colour(1));
+    }
+}
@@ -0,0 +1,326 @@
+// The answers ledger keeps every answer we've submitted, one per line:
+//
+//     <day> <part> <verdict> <answer>
+//
+// where the verdict is one of correct, too-high, too-low or wrong.
+// Blank lines and lines starting with # are ignored.
+use super::lib::Part;
+use super::solution::Answer;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+pub const DEFAULT_PATH: &str = "answers.txt";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Verdict {
+    Correct,
+    TooHigh,
+    TooLow,
+    // Rejected without a hint, e.g. answers that aren't numbers
+    Wrong,
+}
+
+impl Verdict {
+    pub fn parse(value: &str) -> Option<Verdict> {
+        match value {
+            "correct" => Some(Verdict::Correct),
+            "too-high" => Some(Verdict::TooHigh),
+            "too-low" => Some(Verdict::TooLow),
+            "wrong" => Some(Verdict::Wrong),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Verdict::Correct => "correct",
+            Verdict::TooHigh => "too-high",
+            Verdict::TooLow => "too-low",
+            Verdict::Wrong => "wrong",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub day: u8,
+    pub part: Part,
+    pub verdict: Verdict,
+    pub answer: String,
+}
+
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {}",
+            self.day,
+            self.part.number(),
+            self.verdict,
+            self.answer
+        )
+    }
+}
+
+// The answer is the rest of the line, so it can have spaces in it
+fn parse_entry(line: &str) -> Option<Entry> {
+    let mut fields: Vec<&str> = Vec::new();
+    let mut rest = line;
+    while fields.len() < 3 {
+        let (field, tail) = rest.split_once(char::is_whitespace)?;
+        fields.push(field);
+        rest = tail.trim_start();
+    }
+    fields.push(rest);
+    match fields.as_slice() {
+        [day, part, verdict, answer] if !answer.is_empty() => Some(Entry {
+            day: day.parse().ok()?,
+            part: match *part {
+                "1" => Part::One,
+                "2" => Part::Two,
+                _ => return None,
+            },
+            verdict: Verdict::parse(verdict)?,
+            answer: answer.to_string(),
+        }),
+        _ => None,
+    }
+}
+
+// What the ledger has to say about a freshly computed answer
+#[derive(Debug, PartialEq)]
+pub enum Check {
+    // Matches the confirmed answer
+    Confirmed,
+    // There is a confirmed answer and this isn't it
+    Changed { expected: String },
+    // No confirmed answer yet, but this was already rejected
+    AlreadyRejected { verdict: Verdict },
+    // No confirmed answer yet, and a previous guess rules this one out
+    OutOfBounds { guess: String, verdict: Verdict },
+    // Nothing to compare against
+    Unknown,
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Check::Confirmed => write!(f, "matches the confirmed answer"),
+            Check::Changed { expected } => {
+                write!(f, "differs from the confirmed answer {}", expected)
+            }
+            Check::AlreadyRejected { verdict } => {
+                write!(f, "was already submitted and rejected as {}", verdict)
+            }
+            Check::OutOfBounds {
+                guess,
+                verdict: Verdict::TooLow,
+            } => write!(f, "is not above {}, which was too low", guess),
+            Check::OutOfBounds { guess, .. } => {
+                write!(f, "is not below {}, which was too high", guess)
+            }
+            Check::Unknown => write!(f, "has no confirmed answer"),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Ledger {
+    pub entries: Vec<Entry>,
+}
+
+impl Ledger {
+    pub fn parse(input: &str) -> Result<Ledger, String> {
+        let mut entries: Vec<Entry> = Vec::new();
+        for (idx, line) in input.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            match parse_entry(line) {
+                Some(entry) => entries.push(entry),
+                None => return Err(format!("line {}: unable to read {:?}", idx + 1, line)),
+            }
+        }
+        Ok(Ledger { entries })
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Ledger> {
+        let contents = fs::read_to_string(path)?;
+        Ledger::parse(&contents).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    // Appends a single entry, leaving the rest of the file (and its comments)
+    // alone. Every entry is a line, so an answer can't span more than one.
+    pub fn append<P: AsRef<Path>>(path: P, entry: &Entry) -> io::Result<()> {
+        use std::io::Write;
+        if entry.answer.trim().is_empty() || entry.answer.contains(['\n', '\r']) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Unable to record {:?} on a line of its own", entry.answer),
+            ));
+        }
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        writeln!(file, "{}", entry)
+    }
+
+    fn for_part(&self, day: u8, part: Part) -> impl Iterator<Item = &Entry> {
+        self.entries
+            .iter()
+            .filter(move |e| e.day == day && e.part == part)
+    }
+
+    pub fn correct(&self, day: u8, part: Part) -> Option<&str> {
+        self.for_part(day, part)
+            .find(|e| e.verdict == Verdict::Correct)
+            .map(|e| e.answer.as_str())
+    }
+
+    pub fn check(&self, day: u8, part: Part, answer: &Answer) -> Check {
+        let candidate = answer.to_string();
+        if let Some(expected) = self.correct(day, part) {
+            return if expected == candidate {
+                Check::Confirmed
+            } else {
+                Check::Changed {
+                    expected: expected.to_string(),
+                }
+            };
+        }
+
+        if let Some(rejected) = self.for_part(day, part).find(|e| e.answer == candidate) {
+            return Check::AlreadyRejected {
+                verdict: rejected.verdict,
+            };
+        }
+
+        // Only numeric answers have bounds
+        if let Answer::Number(n) = answer {
+            for entry in self.for_part(day, part) {
+                let guess = match entry.answer.parse::<i128>() {
+                    Ok(guess) => guess,
+                    Err(_) => continue,
+                };
+                let ruled_out = match entry.verdict {
+                    Verdict::TooLow => *n <= guess,
+                    Verdict::TooHigh => *n >= guess,
+                    _ => false,
+                };
+                if ruled_out {
+                    return Check::OutOfBounds {
+                        guess: entry.answer.clone(),
+                        verdict: entry.verdict,
+                    };
+                }
+            }
+        }
+
+        Check::Unknown
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LEDGER: &str = "# day part verdict answer
+9 2 too-low 54756
+9 2 too-high 2000000
+9 2 wrong abc
+1 1 correct 1121
+";
+
+    #[test]
+    fn skips_comments_and_blank_lines() {
+        let ledger = Ledger::parse(LEDGER).unwrap();
+        assert_eq!(ledger.entries.len(), 4);
+        assert_eq!(ledger.correct(1, Part::One), Some("1121"));
+        assert_eq!(ledger.correct(1, Part::Two), None);
+    }
+
+    #[test]
+    fn rejects_malformed_entries() {
+        assert!(Ledger::parse("1 3 correct 5").is_err());
+        assert!(Ledger::parse("1 1 maybe 5").is_err());
+        assert!(Ledger::parse("1 1 correct").is_err());
+    }
+
+    #[test]
+    fn answers_with_spaces_read_back() {
+        let path = std::env::temp_dir().join(format!("aoc-2021-ledger-{}.txt", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let entry = |answer: &str| Entry {
+            day: 8,
+            part: Part::Two,
+            verdict: Verdict::Wrong,
+            answer: answer.to_string(),
+        };
+        Ledger::append(&path, &entry("12 34")).unwrap();
+        Ledger::append(&path, &entry("56")).unwrap();
+        assert!(Ledger::append(&path, &entry("7\n8")).is_err());
+        assert!(Ledger::append(&path, &entry(" ")).is_err());
+
+        let ledger = Ledger::load(&path).unwrap();
+        let answers: Vec<&str> = ledger.entries.iter().map(|e| e.answer.as_str()).collect();
+        assert_eq!(answers, vec!["12 34", "56"]);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn compares_against_the_confirmed_answer() {
+        let ledger = Ledger::parse(LEDGER).unwrap();
+        assert_eq!(
+            ledger.check(1, Part::One, &Answer::Number(1121)),
+            Check::Confirmed
+        );
+        assert_eq!(
+            ledger.check(1, Part::One, &Answer::Number(1122)),
+            Check::Changed {
+                expected: "1121".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn uses_earlier_guesses_as_bounds() {
+        let ledger = Ledger::parse(LEDGER).unwrap();
+        assert_eq!(
+            ledger.check(9, Part::Two, &Answer::Number(54756)),
+            Check::AlreadyRejected {
+                verdict: Verdict::TooLow
+            }
+        );
+        assert_eq!(
+            ledger.check(9, Part::Two, &Answer::Number(100)),
+            Check::OutOfBounds {
+                guess: "54756".to_string(),
+                verdict: Verdict::TooLow
+            }
+        );
+        assert_eq!(
+            ledger.check(9, Part::Two, &Answer::Number(3000000)),
+            Check::OutOfBounds {
+                guess: "2000000".to_string(),
+                verdict: Verdict::TooHigh
+            }
+        );
+        assert_eq!(
+            ledger.check(9, Part::Two, &Answer::Number(1123524)),
+            Check::Unknown
+        );
+        assert_eq!(
+            ledger.check(9, Part::Two, &Answer::Text("abc".to_string())),
+            Check::AlreadyRejected {
+                verdict: Verdict::Wrong
+            }
+        );
+    }
+}
@@ -0,0 +1,47 @@
+// The library behind the aoc-2021 binary. Every day's parsers and solvers are
+// public, along with the parsing helpers in `lib` and the registry in
+// `solution`, so other tools can call them directly.
+//
+// src/lib.rs is the `lib` module here rather than the library root, see the
+// [lib] section in Cargo.toml
+#![allow(special_module_name)]
+
+pub mod day1;
+pub mod day10;
+pub mod day2;
+pub mod day3;
+pub mod day4;
+pub mod day5;
+pub mod day6;
+pub mod day7;
+pub mod day8;
+pub mod day9;
+// The stub new-day writes out, which has to build and pass its own test
+#[cfg(test)]
+#[allow(dead_code)]
+#[path = "template.rs"]
+mod day0;
+
+pub mod animate;
+pub mod answers;
+pub mod bench;
+pub mod cli;
+pub mod commands;
+pub mod diff;
+pub mod generate;
+pub mod image;
+pub mod input;
+#[cfg(test)]
+mod laws;
+pub mod lib;
+pub mod mock;
+pub mod pool;
+#[cfg(test)]
+mod property;
+pub mod report;
+pub mod scaffold;
+pub mod solution;
+pub mod submit;
+pub mod trace;
+pub mod watch;
+// mod nalgebra_testing;
@@ -0,0 +1,250 @@
+// Benchmarks run every step many times and keep the results in a history
+// file, one line per step:
+//
+//     <unix time> <day> <step> <min ns> <median ns> <p95 ns>
+//
+// where the step is parse, part1 or part2. Each bench appends its lines, so the
+// last line for a step is what the next bench compares against.
+use super::lib::{Error, Part, Result};
+use super::solution::Runner;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub const DEFAULT_HISTORY: &str = "bench_history.txt";
+pub const DEFAULT_RUNS: usize = 10;
+
+// How much slower the median may get, in percent, before it's a regression
+pub const DEFAULT_THRESHOLD: f64 = 10.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Step {
+    Parse,
+    Solve(Part),
+}
+
+impl Step {
+    pub fn parse(value: &str) -> Option<Step> {
+        match value {
+            "parse" => Some(Step::Parse),
+            "part1" => Some(Step::Solve(Part::One)),
+            "part2" => Some(Step::Solve(Part::Two)),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Step::Parse => write!(f, "parse"),
+            Step::Solve(part) => write!(f, "part{}", part.number()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub p95: Duration,
+}
+
+impl Stats {
+    // Uses the nearest rank, so every statistic is one of the samples
+    pub fn from_samples(samples: &[Duration]) -> Option<Stats> {
+        let mut sorted = samples.to_vec();
+        sorted.sort();
+        let rank = |percentile: usize| {
+            let idx = (sorted.len() * percentile).div_ceil(100);
+            sorted[idx.max(1) - 1]
+        };
+        Some(Stats {
+            min: *sorted.first()?,
+            median: rank(50),
+            p95: rank(95),
+        })
+    }
+
+    // How much slower the median got, in percent
+    pub fn change(&self, previous: &Stats) -> f64 {
+        let before = previous.median.as_nanos() as f64;
+        let after = self.median.as_nanos() as f64;
+        (after - before) / before.max(1.0) * 100.0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    pub timestamp: u64,
+    pub day: u8,
+    pub step: Step,
+    pub stats: Stats,
+}
+
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {} {} {}",
+            self.timestamp,
+            self.day,
+            self.step,
+            self.stats.min.as_nanos(),
+            self.stats.median.as_nanos(),
+            self.stats.p95.as_nanos()
+        )
+    }
+}
+
+fn parse_record(line: &str) -> Option<Record> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    let nanos = |field: &str| field.parse::<u64>().ok().map(Duration::from_nanos);
+    match fields.as_slice() {
+        [timestamp, day, step, min, median, p95] => Some(Record {
+            timestamp: timestamp.parse().ok()?,
+            day: day.parse().ok()?,
+            step: Step::parse(step)?,
+            stats: Stats {
+                min: nanos(min)?,
+                median: nanos(median)?,
+                p95: nanos(p95)?,
+            },
+        }),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct History {
+    pub records: Vec<Record>,
+}
+
+impl History {
+    pub fn parse(input: &str) -> std::result::Result<History, String> {
+        let mut records: Vec<Record> = Vec::new();
+        for (idx, line) in input.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            match parse_record(line) {
+                Some(record) => records.push(record),
+                None => return Err(format!("line {}: unable to read {:?}", idx + 1, line)),
+            }
+        }
+        Ok(History { records })
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<History> {
+        let contents = fs::read_to_string(path)?;
+        History::parse(&contents).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    pub fn append<P: AsRef<Path>>(path: P, records: &[Record]) -> io::Result<()> {
+        use std::io::Write;
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        for record in records {
+            writeln!(file, "{}", record)?;
+        }
+        Ok(())
+    }
+
+    pub fn latest(&self, day: u8, step: Step) -> Option<&Record> {
+        self.records
+            .iter()
+            .rev()
+            .find(|r| r.day == day && r.step == step)
+    }
+}
+
+// Runs the parse and both parts `runs` times. Parts that aren't implemented
+// are left out, any other error stops the benchmark.
+pub fn measure(solution: &dyn Runner, input: &str, runs: usize) -> Result<Vec<(Step, Stats)>> {
+    let steps = [Step::Parse, Step::Solve(Part::One), Step::Solve(Part::Two)];
+    let mut samples: Vec<Vec<Duration>> = vec![Vec::new(); steps.len()];
+    for _ in 0..runs {
+        let run = solution.run(input, &[Part::One, Part::Two])?;
+        samples[0].push(run.parse);
+        for (idx, part_run) in run.parts.into_iter().enumerate() {
+            match part_run.answer {
+                Ok(_) => samples[idx + 1].push(part_run.elapsed),
+                Err(Error::NotImplemented) => (),
+                Err(e) => return Err(e),
+            }
+        }
+    }
+    Ok(steps
+        .iter()
+        .zip(samples)
+        .filter_map(|(step, samples)| Some((*step, Stats::from_samples(&samples)?)))
+        .collect())
+}
+
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn millis(values: &[u64]) -> Vec<Duration> {
+        values.iter().map(|ms| Duration::from_millis(*ms)).collect()
+    }
+
+    #[test]
+    fn computes_nearest_rank_statistics() {
+        let samples = millis(&[9, 1, 8, 2, 7, 3, 6, 4, 5, 100]);
+        let stats = Stats::from_samples(&samples).unwrap();
+        assert_eq!(stats.min, Duration::from_millis(1));
+        assert_eq!(stats.median, Duration::from_millis(5));
+        assert_eq!(stats.p95, Duration::from_millis(100));
+        assert_eq!(Stats::from_samples(&[]), None);
+    }
+
+    #[test]
+    fn a_single_sample_is_every_statistic() {
+        let stats = Stats::from_samples(&millis(&[3])).unwrap();
+        let three = Duration::from_millis(3);
+        assert_eq!((stats.min, stats.median, stats.p95), (three, three, three));
+    }
+
+    #[test]
+    fn compares_medians_in_percent() {
+        let before = Stats::from_samples(&millis(&[10])).unwrap();
+        let after = Stats::from_samples(&millis(&[12])).unwrap();
+        assert!((after.change(&before) - 20.0).abs() < 1e-9);
+        assert!((before.change(&after) + 16.666).abs() < 1e-3);
+    }
+
+    #[test]
+    fn round_trips_records_and_finds_the_latest() {
+        let history = History::parse(
+            "# comment
+100 9 part2 1 2 3
+200 9 part2 4 5 6
+200 9 parse 7 8 9
+",
+        )
+        .unwrap();
+        let line = history.records[1].to_string();
+        assert_eq!(line, "200 9 part2 4 5 6");
+        assert_eq!(
+            History::parse(&line).unwrap().records[0],
+            history.records[1]
+        );
+        let latest = history.latest(9, Step::Solve(Part::Two)).unwrap();
+        assert_eq!(latest.timestamp, 200);
+        assert!(history.latest(9, Step::Solve(Part::One)).is_none());
+        assert!(History::parse("100 9 part3 1 2 3").is_err());
+    }
+}
@@ -0,0 +1,781 @@
+use super::animate;
+use super::answers::{self, Verdict};
+use super::bench;
+use super::generate;
+use super::image;
+use super::input::{self, Source};
+use super::lib::Part;
+use super::mock;
+use super::report::Format;
+use super::submit;
+use super::watch;
+use std::path::PathBuf;
+use std::time::Duration;
+
+pub const USAGE: &str = "Usage:
+    aoc-2021 run --day <1-25> [--part <1|2>] [--input <path|->] [--input-dir <dir>]
+                 [--answers <path>] [--format <text|json>] [--explain]
+    aoc-2021 all [--input-dir <dir>] [--format <text|json>] [--workers <n>]
+    aoc-2021 bench [--day <1-25>] [--runs <n>] [--input-dir <dir>] [--history <path>]
+                   [--threshold <percent>]
+    aoc-2021 diff --day <1-25> [--part <1|2>] [--input <path|->] [--input-dir <dir>]
+    aoc-2021 generate --day <1-25> [--size <n>] [--seed <n>]
+    aoc-2021 watch --day <1-25> [--input-dir <dir>] [--interval <ms>]
+    aoc-2021 animate --day <1-25> [--part <1|2>] [--input <path|->] [--input-dir <dir>]
+                     [--fps <n>]
+    aoc-2021 image --day <1-25> [--input <path|->] [--input-dir <dir>] [--output-dir <dir>]
+                   [--format <png|ppm|pgm>] [--scale <n>]
+    aoc-2021 verify [--input-dir <dir>] [--answers <path>]
+    aoc-2021 record --day <1-25> --part <1|2> --verdict <correct|too-high|too-low|wrong>
+                    --answer <answer> [--answers <path>]
+    aoc-2021 submit --day <1-25> --part <1|2> [--answer <answer>] [--input-dir <dir>]
+                    [--answers <path>] [--endpoint <url>]
+    aoc-2021 mock-server [--port <n>] [--answers <path>] [--cooldown <seconds>]
+    aoc-2021 new-day --day <1-25> [--root <dir>]
+
+Inputs are read from <dir>/day<N>, where <dir> defaults to $AOC_INPUT_DIR or inputs.
+Passing --input - reads the input from stdin.
+--format json prints one JSON object per line for each day and part.
+--explain tells the story of how each answer was found.
+submit posts the answer (solved from the input unless given) to <url>, which
+defaults to $AOC_SUBMIT_URL or the mock server's, and records the verdict in
+the answers. $AOC_SESSION is sent as the session cookie.
+image writes day<N>-<name>.<format> for every picture a day has, by default
+scaled to about 500 pixels across.";
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    // When no part is given, both parts are run
+    //
+    // The answers ledger only applies to our own puzzle input, so there is none
+    // when another input is given
+    Run {
+        day: u8,
+        part: Option<Part>,
+        input: Source,
+        answers: Option<PathBuf>,
+        format: Format,
+        explain: bool,
+    },
+    // Run both parts of every registered day and print a timing table
+    //
+    // The days run in parallel, on one worker per core unless told otherwise
+    All {
+        input_dir: PathBuf,
+        format: Format,
+        workers: Option<usize>,
+    },
+    // Time every step of one or all days, and compare with the previous bench
+    Bench {
+        day: Option<u8>,
+        runs: usize,
+        input_dir: PathBuf,
+        history: PathBuf,
+        threshold: f64,
+    },
+    // Cross-check every implementation of a part on the same input
+    Diff {
+        day: u8,
+        part: Option<Part>,
+        input: Source,
+    },
+    // Print a random input; without a seed one is picked and reported on stderr
+    Generate {
+        day: u8,
+        size: usize,
+        seed: Option<u64>,
+    },
+    // Solve the input and the sample again whenever either changes
+    Watch {
+        day: u8,
+        input_dir: PathBuf,
+        interval: Duration,
+    },
+    // Play the trace events of a run back as frames in the terminal
+    Animate {
+        day: u8,
+        part: Option<Part>,
+        input: Source,
+        fps: u32,
+    },
+    // Write the grids a day draws as image files, each cell scale pixels wide
+    Image {
+        day: u8,
+        input: Source,
+        output_dir: PathBuf,
+        format: image::Format,
+        scale: Option<usize>,
+    },
+    // Fail when any part no longer produces its confirmed answer
+    Verify {
+        input_dir: PathBuf,
+        answers: PathBuf,
+    },
+    // Add a submitted answer to the ledger
+    Record {
+        day: u8,
+        part: Part,
+        verdict: Verdict,
+        answer: String,
+        answers: PathBuf,
+    },
+    // Post an answer to the endpoint and record the verdict. Without an answer,
+    // the day is solved on its input.
+    Submit {
+        day: u8,
+        part: Part,
+        answer: Option<String>,
+        input_dir: PathBuf,
+        answers: PathBuf,
+        endpoint: String,
+    },
+    // Answer submissions locally, checking them against the answers ledger
+    MockServer {
+        port: u16,
+        answers: PathBuf,
+        cooldown: Duration,
+    },
+    // Create and register a new day in the source tree under root
+    NewDay {
+        day: u8,
+        root: PathBuf,
+    },
+}
+
+fn parse_day(value: &str) -> Result<u8, String> {
+    match value.parse::<u8>() {
+        Ok(day) if (1..=25).contains(&day) => Ok(day),
+        _ => Err(format!("Expected a day between 1 and 25, got: {:?}", value)),
+    }
+}
+
+fn parse_part(value: &str) -> Result<Part, String> {
+    match value {
+        "1" => Ok(Part::One),
+        "2" => Ok(Part::Two),
+        _ => Err(format!("Expected part 1 or 2, got: {:?}", value)),
+    }
+}
+
+fn parse_verdict(value: &str) -> Result<Verdict, String> {
+    Verdict::parse(value).ok_or_else(|| {
+        format!(
+            "Expected correct, too-high, too-low or wrong, got: {:?}",
+            value
+        )
+    })
+}
+
+fn parse_format(value: &str) -> Result<Format, String> {
+    Format::parse(value).ok_or_else(|| format!("Expected text or json, got: {:?}", value))
+}
+
+fn parse_runs(value: &str) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(runs) if runs > 0 => Ok(runs),
+        _ => Err(format!(
+            "Expected a positive number of runs, got: {:?}",
+            value
+        )),
+    }
+}
+
+fn parse_size(value: &str) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(size) if size > 0 => Ok(size),
+        _ => Err(format!("Expected a positive size, got: {:?}", value)),
+    }
+}
+
+fn parse_workers(value: &str) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(workers) if workers > 0 => Ok(workers),
+        _ => Err(format!(
+            "Expected a positive number of workers, got: {:?}",
+            value
+        )),
+    }
+}
+
+fn parse_seed(value: &str) -> Result<u64, String> {
+    value
+        .parse::<u64>()
+        .map_err(|_| format!("Expected a number for the seed, got: {:?}", value))
+}
+
+fn parse_interval(value: &str) -> Result<Duration, String> {
+    match value.parse::<u64>() {
+        Ok(millis) if millis > 0 => Ok(Duration::from_millis(millis)),
+        _ => Err(format!(
+            "Expected a positive number of milliseconds, got: {:?}",
+            value
+        )),
+    }
+}
+
+fn parse_fps(value: &str) -> Result<u32, String> {
+    match value.parse::<u32>() {
+        Ok(fps) if fps > 0 => Ok(fps),
+        _ => Err(format!(
+            "Expected a positive number of frames per second, got: {:?}",
+            value
+        )),
+    }
+}
+
+fn parse_image_format(value: &str) -> Result<image::Format, String> {
+    image::Format::parse(value).ok_or_else(|| format!("Expected png, ppm or pgm, got: {:?}", value))
+}
+
+fn parse_scale(value: &str) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(scale) if scale > 0 => Ok(scale),
+        _ => Err(format!(
+            "Expected a positive number of pixels, got: {:?}",
+            value
+        )),
+    }
+}
+
+fn parse_port(value: &str) -> Result<u16, String> {
+    value
+        .parse::<u16>()
+        .map_err(|_| format!("Expected a port number, got: {:?}", value))
+}
+
+fn parse_cooldown(value: &str) -> Result<Duration, String> {
+    value
+        .parse::<u64>()
+        .map(Duration::from_secs)
+        .map_err(|_| format!("Expected a number of seconds, got: {:?}", value))
+}
+
+fn parse_threshold(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(threshold) if threshold >= 0.0 => Ok(threshold),
+        _ => Err(format!("Expected a percentage, got: {:?}", value)),
+    }
+}
+
+// Options that are on or off, and so take no value
+const SWITCHES: &[&str] = &["--explain"];
+
+// Every other option takes a value, e.g. --day 4
+struct Flags<'a> {
+    command: &'a str,
+    values: Vec<(&'a str, &'a str)>,
+    switches: Vec<&'a str>,
+}
+
+impl<'a> Flags<'a> {
+    fn new(command: &'a str, args: &'a [String]) -> Result<Flags<'a>, String> {
+        let mut values: Vec<(&str, &str)> = Vec::new();
+        let mut switches: Vec<&str> = Vec::new();
+        let mut iter = args.iter();
+        while let Some(flag) = iter.next() {
+            if SWITCHES.contains(&flag.as_str()) {
+                switches.push(flag);
+                continue;
+            }
+            let value = iter
+                .next()
+                .ok_or_else(|| format!("Missing a value for {}", flag))?;
+            values.push((flag, value));
+        }
+        Ok(Flags {
+            command,
+            values,
+            switches,
+        })
+    }
+
+    fn switch(&mut self, name: &str) -> bool {
+        let before = self.switches.len();
+        self.switches.retain(|s| *s != name);
+        self.switches.len() != before
+    }
+
+    fn take(&mut self, names: &[&str]) -> Option<&'a str> {
+        let idx = self.values.iter().position(|(f, _)| names.contains(f))?;
+        Some(self.values.remove(idx).1)
+    }
+
+    fn require(&mut self, names: &[&str]) -> Result<&'a str, String> {
+        self.take(names)
+            .ok_or_else(|| format!("The {} command requires {}", self.command, names[0]))
+    }
+
+    fn input_dir(&mut self) -> PathBuf {
+        input::input_dir(self.take(&["--input-dir"]))
+    }
+
+    fn format(&mut self) -> Result<Format, String> {
+        self.take(&["--format", "-f"])
+            .map(parse_format)
+            .unwrap_or(Ok(Format::Text))
+    }
+
+    fn answers(&mut self) -> PathBuf {
+        PathBuf::from(self.take(&["--answers"]).unwrap_or(answers::DEFAULT_PATH))
+    }
+
+    // Anything left over wasn't recognized by the command
+    fn finish(self) -> Result<(), String> {
+        match (self.values.first(), self.switches.first()) {
+            (Some((flag, _)), _) | (None, Some(flag)) => Err(format!("Unknown option: {}", flag)),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+fn parse_run(mut flags: Flags) -> Result<Command, String> {
+    let day = parse_day(flags.require(&["--day", "-d"])?)?;
+    let part = flags.take(&["--part", "-p"]).map(parse_part).transpose()?;
+    let input_dir = flags.input_dir();
+    let answers = flags.answers();
+    let format = flags.format()?;
+    let explain = flags.switch("--explain");
+    let (input, answers) = match flags.take(&["--input", "-i"]) {
+        Some(arg) => (Source::from_arg(arg), None),
+        None => (
+            Source::File(input::input_path(&input_dir, day)),
+            Some(answers),
+        ),
+    };
+    flags.finish()?;
+    Ok(Command::Run {
+        day,
+        part,
+        input,
+        answers,
+        format,
+        explain,
+    })
+}
+
+fn parse_all(mut flags: Flags) -> Result<Command, String> {
+    let input_dir = flags.input_dir();
+    let format = flags.format()?;
+    let workers = flags
+        .take(&["--workers", "-j"])
+        .map(parse_workers)
+        .transpose()?;
+    flags.finish()?;
+    Ok(Command::All {
+        input_dir,
+        format,
+        workers,
+    })
+}
+
+fn parse_bench(mut flags: Flags) -> Result<Command, String> {
+    let day = flags.take(&["--day", "-d"]).map(parse_day).transpose()?;
+    let runs = flags
+        .take(&["--runs", "-n"])
+        .map(parse_runs)
+        .transpose()?
+        .unwrap_or(bench::DEFAULT_RUNS);
+    let input_dir = flags.input_dir();
+    let history = PathBuf::from(flags.take(&["--history"]).unwrap_or(bench::DEFAULT_HISTORY));
+    let threshold = flags
+        .take(&["--threshold"])
+        .map(parse_threshold)
+        .transpose()?
+        .unwrap_or(bench::DEFAULT_THRESHOLD);
+    flags.finish()?;
+    Ok(Command::Bench {
+        day,
+        runs,
+        input_dir,
+        history,
+        threshold,
+    })
+}
+
+fn parse_diff(mut flags: Flags) -> Result<Command, String> {
+    let day = parse_day(flags.require(&["--day", "-d"])?)?;
+    let part = flags.take(&["--part", "-p"]).map(parse_part).transpose()?;
+    let input_dir = flags.input_dir();
+    let input = match flags.take(&["--input", "-i"]) {
+        Some(arg) => Source::from_arg(arg),
+        None => Source::File(input::input_path(&input_dir, day)),
+    };
+    flags.finish()?;
+    Ok(Command::Diff { day, part, input })
+}
+
+fn parse_generate(mut flags: Flags) -> Result<Command, String> {
+    let day = parse_day(flags.require(&["--day", "-d"])?)?;
+    let size = flags
+        .take(&["--size", "-n"])
+        .map(parse_size)
+        .transpose()?
+        .unwrap_or(generate::DEFAULT_SIZE);
+    let seed = flags.take(&["--seed"]).map(parse_seed).transpose()?;
+    flags.finish()?;
+    Ok(Command::Generate { day, size, seed })
+}
+
+fn parse_watch(mut flags: Flags) -> Result<Command, String> {
+    let day = parse_day(flags.require(&["--day", "-d"])?)?;
+    let input_dir = flags.input_dir();
+    let interval = flags
+        .take(&["--interval"])
+        .map(parse_interval)
+        .transpose()?
+        .unwrap_or(watch::DEFAULT_INTERVAL);
+    flags.finish()?;
+    Ok(Command::Watch {
+        day,
+        input_dir,
+        interval,
+    })
+}
+
+fn parse_animate(mut flags: Flags) -> Result<Command, String> {
+    let day = parse_day(flags.require(&["--day", "-d"])?)?;
+    let part = flags.take(&["--part", "-p"]).map(parse_part).transpose()?;
+    let input_dir = flags.input_dir();
+    let input = match flags.take(&["--input", "-i"]) {
+        Some(arg) => Source::from_arg(arg),
+        None => Source::File(input::input_path(&input_dir, day)),
+    };
+    let fps = flags
+        .take(&["--fps"])
+        .map(parse_fps)
+        .transpose()?
+        .unwrap_or(animate::DEFAULT_FPS);
+    flags.finish()?;
+    Ok(Command::Animate {
+        day,
+        part,
+        input,
+        fps,
+    })
+}
+
+fn parse_image(mut flags: Flags) -> Result<Command, String> {
+    let day = parse_day(flags.require(&["--day", "-d"])?)?;
+    let input_dir = flags.input_dir();
+    let input = match flags.take(&["--input", "-i"]) {
+        Some(arg) => Source::from_arg(arg),
+        None => Source::File(input::input_path(&input_dir, day)),
+    };
+    let output_dir = PathBuf::from(flags.take(&["--output-dir", "-o"]).unwrap_or("."));
+    let format = flags
+        .take(&["--format", "-f"])
+        .map(parse_image_format)
+        .transpose()?
+        .unwrap_or(image::Format::Png);
+    let scale = flags.take(&["--scale"]).map(parse_scale).transpose()?;
+    flags.finish()?;
+    Ok(Command::Image {
+        day,
+        input,
+        output_dir,
+        format,
+        scale,
+    })
+}
+
+fn parse_verify(mut flags: Flags) -> Result<Command, String> {
+    let input_dir = flags.input_dir();
+    let answers = flags.answers();
+    flags.finish()?;
+    Ok(Command::Verify { input_dir, answers })
+}
+
+fn parse_record(mut flags: Flags) -> Result<Command, String> {
+    let day = parse_day(flags.require(&["--day", "-d"])?)?;
+    let part = parse_part(flags.require(&["--part", "-p"])?)?;
+    let verdict = parse_verdict(flags.require(&["--verdict"])?)?;
+    let answer = flags.require(&["--answer"])?.to_string();
+    let answers = flags.answers();
+    flags.finish()?;
+    Ok(Command::Record {
+        day,
+        part,
+        verdict,
+        answer,
+        answers,
+    })
+}
+
+fn parse_submit(mut flags: Flags) -> Result<Command, String> {
+    let day = parse_day(flags.require(&["--day", "-d"])?)?;
+    let part = parse_part(flags.require(&["--part", "-p"])?)?;
+    let answer = flags.take(&["--answer"]).map(String::from);
+    let input_dir = flags.input_dir();
+    let answers = flags.answers();
+    let endpoint = submit::endpoint(flags.take(&["--endpoint"]));
+    flags.finish()?;
+    Ok(Command::Submit {
+        day,
+        part,
+        answer,
+        input_dir,
+        answers,
+        endpoint,
+    })
+}
+
+fn parse_mock_server(mut flags: Flags) -> Result<Command, String> {
+    let port = flags
+        .take(&["--port"])
+        .map(parse_port)
+        .transpose()?
+        .unwrap_or(mock::DEFAULT_PORT);
+    let answers = flags.answers();
+    let cooldown = flags
+        .take(&["--cooldown"])
+        .map(parse_cooldown)
+        .transpose()?
+        .unwrap_or(mock::DEFAULT_COOLDOWN);
+    flags.finish()?;
+    Ok(Command::MockServer {
+        port,
+        answers,
+        cooldown,
+    })
+}
+
+fn parse_new_day(mut flags: Flags) -> Result<Command, String> {
+    let day = parse_day(flags.require(&["--day", "-d"])?)?;
+    let root = PathBuf::from(flags.take(&["--root"]).unwrap_or("."));
+    flags.finish()?;
+    Ok(Command::NewDay { day, root })
+}
+
+pub fn parse(args: &[String]) -> Result<Command, String> {
+    let (command, rest) = match args {
+        [command, rest @ ..] => (command.as_str(), rest),
+        [] => return Err("Missing a command".to_string()),
+    };
+    let flags = Flags::new(command, rest)?;
+    match command {
+        "run" => parse_run(flags),
+        "all" => parse_all(flags),
+        "bench" => parse_bench(flags),
+        "diff" => parse_diff(flags),
+        "generate" => parse_generate(flags),
+        "watch" => parse_watch(flags),
+        "animate" => parse_animate(flags),
+        "image" => parse_image(flags),
+        "verify" => parse_verify(flags),
+        "record" => parse_record(flags),
+        "submit" => parse_submit(flags),
+        "mock-server" => parse_mock_server(flags),
+        "new-day" => parse_new_day(flags),
+        _ => Err(format!("Unknown command: {}", command)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn run_defaults_to_both_parts_and_the_input_dir() {
+        let command = parse(&args("run --day 4 --input-dir puzzles")).unwrap();
+        assert_eq!(
+            command,
+            Command::Run {
+                day: 4,
+                part: None,
+                input: Source::File(PathBuf::from("puzzles/day4")),
+                answers: Some(PathBuf::from(answers::DEFAULT_PATH)),
+                format: Format::Text,
+                explain: false,
+            }
+        );
+    }
+
+    #[test]
+    fn run_skips_the_ledger_for_other_inputs() {
+        let command = parse(&args("run -d 2 -p 2 --explain -i - --format json")).unwrap();
+        assert_eq!(
+            command,
+            Command::Run {
+                day: 2,
+                part: Some(Part::Two),
+                input: Source::Stdin,
+                answers: None,
+                format: Format::Json,
+                explain: true,
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_bad_arguments() {
+        assert!(parse(&args("")).is_err());
+        assert!(parse(&args("fly")).is_err());
+        assert!(parse(&args("run")).is_err());
+        assert!(parse(&args("run --day 26")).is_err());
+        assert!(parse(&args("run --day 1 --part 3")).is_err());
+        assert!(parse(&args("run --day 1 --part")).is_err());
+        assert!(parse(&args("all --day 1")).is_err());
+        assert!(parse(&args("all --format yaml")).is_err());
+        assert!(parse(&args("all --workers 0")).is_err());
+        assert!(parse(&args("all --explain")).is_err());
+    }
+
+    #[test]
+    fn all_takes_a_worker_count() {
+        let command = parse(&args("all -j 3 --input-dir inputs")).unwrap();
+        assert_eq!(
+            command,
+            Command::All {
+                input_dir: PathBuf::from("inputs"),
+                format: Format::Text,
+                workers: Some(3),
+            }
+        );
+    }
+
+    #[test]
+    fn bench_defaults_to_every_day() {
+        let command = parse(&args("bench --threshold 5 --input-dir inputs")).unwrap();
+        assert_eq!(
+            command,
+            Command::Bench {
+                day: None,
+                runs: bench::DEFAULT_RUNS,
+                input_dir: PathBuf::from("inputs"),
+                history: PathBuf::from(bench::DEFAULT_HISTORY),
+                threshold: 5.0,
+            }
+        );
+        assert!(parse(&args("bench --runs 0")).is_err());
+        assert!(parse(&args("bench --threshold -1")).is_err());
+    }
+
+    #[test]
+    fn diff_reads_the_day_input_by_default() {
+        let command = parse(&args("diff --day 1 --input-dir puzzles")).unwrap();
+        assert_eq!(
+            command,
+            Command::Diff {
+                day: 1,
+                part: None,
+                input: Source::File(PathBuf::from("puzzles/day1")),
+            }
+        );
+        assert!(parse(&args("diff --part 2")).is_err());
+    }
+
+    #[test]
+    fn generate_takes_an_optional_seed() {
+        let command = parse(&args("generate --day 9 --seed 42")).unwrap();
+        assert_eq!(
+            command,
+            Command::Generate {
+                day: 9,
+                size: generate::DEFAULT_SIZE,
+                seed: Some(42),
+            }
+        );
+        assert!(parse(&args("generate --day 9 --size 0")).is_err());
+        assert!(parse(&args("generate --day 9 --seed -1")).is_err());
+    }
+
+    #[test]
+    fn watch_polls_twice_a_second_by_default() {
+        let command = parse(&args("watch --day 3 --input-dir puzzles")).unwrap();
+        assert_eq!(
+            command,
+            Command::Watch {
+                day: 3,
+                input_dir: PathBuf::from("puzzles"),
+                interval: watch::DEFAULT_INTERVAL,
+            }
+        );
+        assert!(parse(&args("watch --day 3 --interval 0")).is_err());
+    }
+
+    #[test]
+    fn animate_takes_a_speed() {
+        let command = parse(&args("animate --day 9 -p 2 --fps 30 -i -")).unwrap();
+        assert_eq!(
+            command,
+            Command::Animate {
+                day: 9,
+                part: Some(Part::Two),
+                input: Source::Stdin,
+                fps: 30,
+            }
+        );
+        assert!(parse(&args("animate --day 9 --fps 0")).is_err());
+    }
+
+    #[test]
+    fn image_defaults_to_png_in_the_current_dir() {
+        let command = parse(&args("image --day 9 --input-dir puzzles")).unwrap();
+        assert_eq!(
+            command,
+            Command::Image {
+                day: 9,
+                input: Source::File(PathBuf::from("puzzles/day9")),
+                output_dir: PathBuf::from("."),
+                format: image::Format::Png,
+                scale: None,
+            }
+        );
+        let command = parse(&args("image -d 5 -o out -f pgm --scale 2")).unwrap();
+        assert!(matches!(
+            command,
+            Command::Image {
+                format: image::Format::Pgm,
+                scale: Some(2),
+                ..
+            }
+        ));
+        assert!(parse(&args("image --day 9 --format gif")).is_err());
+    }
+
+    #[test]
+    fn record_requires_a_verdict_and_answer() {
+        assert!(parse(&args("record --day 1 --part 1 --answer 5")).is_err());
+        let command = parse(&args("record -d 9 -p 2 --verdict too-low --answer 54756")).unwrap();
+        assert_eq!(
+            command,
+            Command::Record {
+                day: 9,
+                part: Part::Two,
+                verdict: Verdict::TooLow,
+                answer: "54756".to_string(),
+                answers: PathBuf::from(answers::DEFAULT_PATH),
+            }
+        );
+    }
+
+    #[test]
+    fn submit_solves_the_day_unless_given_an_answer() {
+        let command = parse(&args("submit -d 9 -p 2 --endpoint http://localhost:9000")).unwrap();
+        assert!(matches!(
+            command,
+            Command::Submit {
+                day: 9,
+                part: Part::Two,
+                answer: None,
+                ref endpoint,
+                ..
+            } if endpoint == "http://localhost:9000"
+        ));
+        assert!(parse(&args("submit --day 9")).is_err());
+        let command = parse(&args("mock-server --port 0 --cooldown 5")).unwrap();
+        assert_eq!(
+            command,
+            Command::MockServer {
+                port: 0,
+                answers: PathBuf::from(answers::DEFAULT_PATH),
+                cooldown: Duration::from_secs(5),
+            }
+        );
+        assert!(parse(&args("mock-server --port 70000")).is_err());
+    }
+}
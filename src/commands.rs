@@ -0,0 +1,811 @@
+// What each command does, once the command line has been parsed
+use super::answers::{Check, Entry, Ledger, Verdict};
+use super::bench::{History, Record};
+use super::cli::Command;
+use super::generate::Rng;
+use super::image::{self, Image};
+use super::input::{self, Source};
+use super::lib::{Error, Part};
+use super::mock::Mock;
+use super::report::Format;
+use super::solution::{Answer, Run};
+use super::submit::{self, Endpoint, Outcome};
+use super::watch::Watcher;
+use super::{animate, bench, diff, pool, report, scaffold, solution};
+use std::env;
+use std::io;
+use std::net::TcpListener;
+use std::path::Path;
+use std::process::ExitCode;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// A missing ledger just means nothing has been submitted yet
+fn load_ledger(path: &Path) -> Result<Ledger, String> {
+    match Ledger::load(path) {
+        Ok(ledger) => Ok(ledger),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Ledger::default()),
+        Err(e) => Err(format!("Unable to read {}: {}", path.display(), e)),
+    }
+}
+
+fn load_input(source: &Source) -> Result<String, String> {
+    source
+        .load()
+        .map_err(|e| format!("Unable to read {}: {}", source, e))
+}
+
+// Parse errors point into the input, so they get rendered like a compiler error
+fn describe_error(source: &Source, e: &Error) -> String {
+    match e {
+        Error::Parse(e) => e.render(&source.to_string()),
+        e => format!("{}: {}", source, e),
+    }
+}
+
+fn run_day(
+    day: u8,
+    part: Option<Part>,
+    source: &Source,
+    answers: Option<&Path>,
+    format: Format,
+    explain: bool,
+) -> ExitCode {
+    let entry = match solution::find(day) {
+        Some(entry) => entry,
+        None => {
+            eprintln!("Day {} is not implemented", day);
+            return ExitCode::FAILURE;
+        }
+    };
+    let ledger = match answers.map(load_ledger).unwrap_or(Ok(Ledger::default())) {
+        Ok(ledger) => ledger,
+        Err(message) => {
+            eprintln!("{}", message);
+            return ExitCode::FAILURE;
+        }
+    };
+    let input = match load_input(source) {
+        Ok(input) => input,
+        Err(message) => {
+            eprintln!("{}", message);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let parts = match part {
+        Some(part) => vec![part],
+        None => vec![Part::One, Part::Two],
+    };
+    let run = if explain {
+        entry.solution.explain(&input, &parts)
+    } else {
+        entry.solution.run(&input, &parts)
+    };
+    if format == Format::Json {
+        report::print_json(day, &parts, &run, source);
+        return match run {
+            Ok(run) if !run.failed() => ExitCode::SUCCESS,
+            _ => ExitCode::FAILURE,
+        };
+    }
+    let run = match run {
+        Ok(run) => run,
+        Err(e) => {
+            eprintln!("Day {}: {}", day, describe_error(source, &e));
+            return ExitCode::FAILURE;
+        }
+    };
+    let mut failed = false;
+    for part_run in run.parts {
+        let part = part_run.part;
+        if explain {
+            report::print_events(&part_run.events);
+        }
+        match part_run.answer {
+            Ok(answer) => {
+                println!("Day {}, Part {}: {}", day, part.number(), answer);
+                match ledger.check(day, part, &answer) {
+                    Check::Confirmed | Check::Unknown => (),
+                    check => eprintln!(
+                        "Warning: day {}, part {}: {} {}",
+                        day,
+                        part.number(),
+                        answer,
+                        check
+                    ),
+                }
+            }
+            Err(Error::NotImplemented) => {
+                println!("Day {}, Part {}: not implemented", day, part.number())
+            }
+            Err(e) => {
+                failed = true;
+                eprintln!("Day {}, Part {}: {}", day, part.number(), e);
+            }
+        }
+    }
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+// Days are solved in parallel, then reported in day order
+fn run_all(input_dir: &Path, format: Format, workers: usize) -> ExitCode {
+    let parts = [Part::One, Part::Two];
+    let start = Instant::now();
+    let results = pool::map(solution::REGISTRY, workers, |entry| {
+        let source = Source::File(input::input_path(input_dir, entry.number));
+        let run = load_input(&source).map(|input| entry.solution.run(&input, &parts));
+        (source, run)
+    });
+    let wall = start.elapsed();
+
+    let mut runs: Vec<(u8, Run)> = Vec::new();
+    let mut failed = false;
+    for (entry, (source, run)) in solution::REGISTRY.iter().zip(results) {
+        let run = match run {
+            Ok(run) => run,
+            Err(message) => {
+                eprintln!("Skipping day {}: {}", entry.number, message);
+                continue;
+            }
+        };
+        if format == Format::Json {
+            report::print_json(entry.number, &parts, &run, &source);
+        }
+        match run {
+            Ok(run) => {
+                failed |= run.failed();
+                runs.push((entry.number, run));
+            }
+            Err(e) => {
+                failed = true;
+                if format == Format::Text {
+                    eprintln!("Day {}: {}", entry.number, describe_error(&source, &e));
+                }
+            }
+        }
+    }
+    if format == Format::Text {
+        report::print_table(&runs, wall, workers);
+    }
+
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn run_bench(
+    day: Option<u8>,
+    runs: usize,
+    input_dir: &Path,
+    history: &Path,
+    threshold: f64,
+) -> ExitCode {
+    let previous = match History::load(history) {
+        Ok(previous) => previous,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => History::default(),
+        Err(e) => {
+            eprintln!("Unable to read {}: {}", history.display(), e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let timestamp = bench::now();
+    let mut rows = Vec::new();
+    let mut records: Vec<Record> = Vec::new();
+    let mut failed = false;
+    let days = solution::REGISTRY
+        .iter()
+        .filter(|entry| day.is_none_or(|day| day == entry.number));
+    for entry in days {
+        let source = Source::File(input::input_path(input_dir, entry.number));
+        let input = match load_input(&source) {
+            Ok(input) => input,
+            Err(message) => {
+                eprintln!("Skipping day {}: {}", entry.number, message);
+                continue;
+            }
+        };
+        let steps = match bench::measure(entry.solution, &input, runs) {
+            Ok(steps) => steps,
+            Err(e) => {
+                failed = true;
+                eprintln!("Day {}: {}", entry.number, describe_error(&source, &e));
+                continue;
+            }
+        };
+        for (step, stats) in steps {
+            let last = previous.latest(entry.number, step).map(|r| r.stats);
+            rows.push((entry.number, step, stats, last));
+            records.push(Record {
+                timestamp,
+                day: entry.number,
+                step,
+                stats,
+            });
+        }
+    }
+    report::print_bench(&rows, threshold);
+
+    if let Err(e) = History::append(history, &records) {
+        eprintln!("Unable to write {}: {}", history.display(), e);
+        return ExitCode::FAILURE;
+    }
+
+    let regressions = rows
+        .iter()
+        .filter(|(_, _, stats, last)| last.is_some_and(|last| stats.change(&last) > threshold))
+        .count();
+    if regressions > 0 {
+        eprintln!(
+            "{} step(s) got more than {}% slower since the last bench",
+            regressions, threshold
+        );
+    }
+    if failed || regressions > 0 {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn run_diff(day: u8, part: Option<Part>, source: &Source) -> ExitCode {
+    let entry = match solution::find(day) {
+        Some(entry) => entry,
+        None => {
+            eprintln!("Day {} is not implemented", day);
+            return ExitCode::FAILURE;
+        }
+    };
+    let input = match load_input(source) {
+        Ok(input) => input,
+        Err(message) => {
+            eprintln!("{}", message);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let parts = match part {
+        Some(part) => vec![part],
+        None => vec![Part::One, Part::Two],
+    };
+    // Panics are reported as outcomes, the default hook would print one per
+    // prefix. It's only silenced while comparing, so other panics still show.
+    let hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(|_| ()));
+    let mut failed = false;
+    let mut error = None;
+    for part in parts {
+        match diff::compare(entry.solution, &input, part) {
+            Ok(comparison) => {
+                // Outcomes that differ only on the full input still count
+                failed |= comparison.outcomes.windows(2).any(|p| p[0].1 != p[1].1);
+                report::print_comparison(day, &comparison);
+            }
+            Err(e) => {
+                error = Some(e);
+                break;
+            }
+        }
+    }
+    std::panic::set_hook(hook);
+
+    if let Some(e) = error {
+        eprintln!("Day {}: {}", day, describe_error(source, &e));
+        return ExitCode::FAILURE;
+    }
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn run_generate(day: u8, size: usize, seed: Option<u64>) -> ExitCode {
+    let entry = match solution::find(day) {
+        Some(entry) => entry,
+        None => {
+            eprintln!("Day {} is not implemented", day);
+            return ExitCode::FAILURE;
+        }
+    };
+    // Report a picked seed so an interesting input can be made again
+    let seed = seed.unwrap_or_else(|| {
+        let seed = bench::now();
+        eprintln!("seed: {}", seed);
+        seed
+    });
+    match entry.solution.generate(&mut Rng::new(seed), size) {
+        Some(input) => {
+            print!("{}", input);
+            ExitCode::SUCCESS
+        }
+        None => {
+            eprintln!("Day {} has no input generator", day);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+// Runs until interrupted
+fn watch(day: u8, input_dir: &Path, interval: Duration) -> ExitCode {
+    let entry = match solution::find(day) {
+        Some(entry) => entry,
+        None => {
+            eprintln!("Day {} is not implemented", day);
+            return ExitCode::FAILURE;
+        }
+    };
+    let mut watcher = Watcher::new(vec![
+        input::input_path(input_dir, day),
+        input::sample_path(input_dir, day),
+    ]);
+    let paths: Vec<String> = watcher.paths().map(|p| p.display().to_string()).collect();
+    eprintln!("Watching {}, press Ctrl-C to stop", paths.join(" and "));
+    loop {
+        for path in watcher.poll() {
+            let source = Source::File(path);
+            let run = load_input(&source).and_then(|input| {
+                entry
+                    .solution
+                    .run(&input, &[Part::One, Part::Two])
+                    .map_err(|e| describe_error(&source, &e))
+            });
+            match run {
+                Ok(run) => report::print_run(&source, &run),
+                Err(message) => eprintln!("{}", message),
+            }
+            println!();
+        }
+        thread::sleep(interval);
+    }
+}
+
+fn animate(day: u8, part: Option<Part>, source: &Source, fps: u32) -> ExitCode {
+    let entry = match solution::find(day) {
+        Some(entry) => entry,
+        None => {
+            eprintln!("Day {} is not implemented", day);
+            return ExitCode::FAILURE;
+        }
+    };
+    let input = match load_input(source) {
+        Ok(input) => input,
+        Err(message) => {
+            eprintln!("{}", message);
+            return ExitCode::FAILURE;
+        }
+    };
+    let parts = match part {
+        Some(part) => vec![part],
+        None => vec![Part::One, Part::Two],
+    };
+    let run = match entry.solution.explain(&input, &parts) {
+        Ok(run) => run,
+        Err(e) => {
+            eprintln!("Day {}: {}", day, describe_error(source, &e));
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut frames: Vec<String> = Vec::new();
+    for part_run in &run.parts {
+        let title = format!("Day {}, Part {}", day, part_run.part.number());
+        frames.extend(animate::frames(&title, &part_run.events));
+    }
+    if frames.is_empty() {
+        eprintln!("Day {} has nothing to animate", day);
+    } else if let Err(e) = animate::play(&frames, fps) {
+        eprintln!("Unable to animate: {}", e);
+        return ExitCode::FAILURE;
+    }
+    for part_run in &run.parts {
+        match &part_run.answer {
+            Ok(answer) => println!("Day {}, Part {}: {}", day, part_run.part.number(), answer),
+            Err(e) => println!("Day {}, Part {}: {}", day, part_run.part.number(), e),
+        }
+    }
+    if run.failed() {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+// Big enough to see every cell, without a 1000x1000 grid taking up gigabytes
+const IMAGE_SIZE: usize = 500;
+
+fn write_images(
+    day: u8,
+    source: &Source,
+    output_dir: &Path,
+    format: image::Format,
+    scale: Option<usize>,
+) -> ExitCode {
+    let entry = match solution::find(day) {
+        Some(entry) => entry,
+        None => {
+            eprintln!("Day {} is not implemented", day);
+            return ExitCode::FAILURE;
+        }
+    };
+    let input = match load_input(source) {
+        Ok(input) => input,
+        Err(message) => {
+            eprintln!("{}", message);
+            return ExitCode::FAILURE;
+        }
+    };
+    let pictures = match entry.solution.pictures(&input) {
+        Ok(pictures) => pictures,
+        Err(e) => {
+            eprintln!("Day {}: {}", day, describe_error(source, &e));
+            return ExitCode::FAILURE;
+        }
+    };
+    if pictures.is_empty() {
+        eprintln!("Day {} has nothing to draw", day);
+        return ExitCode::FAILURE;
+    }
+
+    for picture in pictures {
+        let cells = picture
+            .grid
+            .iter()
+            .map(Vec::len)
+            .chain([picture.grid.len()])
+            .max()
+            .unwrap_or(1);
+        let scale = scale.unwrap_or((IMAGE_SIZE / cells.max(1)).max(1));
+        let path = output_dir.join(format!(
+            "day{}-{}.{}",
+            day,
+            picture.name,
+            format.extension()
+        ));
+        let image = Image::draw(&picture, scale);
+        if let Err(e) = image.save(&path, format) {
+            eprintln!("Unable to write {}: {}", path.display(), e);
+            return ExitCode::FAILURE;
+        }
+        println!(
+            "Wrote {} ({}x{})",
+            path.display(),
+            image.width,
+            image.height
+        );
+    }
+    ExitCode::SUCCESS
+}
+
+fn verify(input_dir: &Path, answers: &Path) -> ExitCode {
+    let ledger = match Ledger::load(answers) {
+        Ok(ledger) => ledger,
+        Err(e) => {
+            eprintln!("Unable to read {}: {}", answers.display(), e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut failures = 0;
+    for entry in solution::REGISTRY {
+        let source = Source::File(input::input_path(input_dir, entry.number));
+        let input = match load_input(&source) {
+            Ok(input) => input,
+            Err(message) => {
+                failures += 1;
+                println!("Day {}: FAILED, {}", entry.number, message);
+                continue;
+            }
+        };
+        let run = match entry.solution.run(&input, &[Part::One, Part::Two]) {
+            Ok(run) => run,
+            Err(e) => {
+                failures += 1;
+                println!(
+                    "Day {}: FAILED, {}",
+                    entry.number,
+                    describe_error(&source, &e)
+                );
+                continue;
+            }
+        };
+        for part_run in run.parts {
+            let label = format!("Day {}, Part {}", entry.number, part_run.part.number());
+            let expected = ledger.correct(entry.number, part_run.part);
+            match (&part_run.answer, expected) {
+                (Ok(answer), _) => match ledger.check(entry.number, part_run.part, answer) {
+                    Check::Confirmed => println!("{}: ok", label),
+                    Check::Changed { expected } => {
+                        failures += 1;
+                        println!(
+                            "{}: FAILED, expected {} but got {}",
+                            label, expected, answer
+                        );
+                    }
+                    check => println!("{}: {} {}", label, answer, check),
+                },
+                (Err(Error::NotImplemented), None) => println!("{}: not implemented", label),
+                (Err(e), Some(expected)) => {
+                    failures += 1;
+                    println!("{}: FAILED, expected {} but {}", label, expected, e);
+                }
+                (Err(e), None) => {
+                    failures += 1;
+                    println!("{}: FAILED, {}", label, e);
+                }
+            }
+        }
+    }
+
+    if failures > 0 {
+        eprintln!(
+            "{} part(s) no longer produce the confirmed answer",
+            failures
+        );
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn record(entry: Entry, answers: &Path) -> ExitCode {
+    match Ledger::append(answers, &entry) {
+        Ok(()) => {
+            println!("Recorded: {}", entry);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("Unable to write {}: {}", answers.display(), e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn submit_answer(
+    day: u8,
+    part: Part,
+    answer: Option<String>,
+    input_dir: &Path,
+    answers: &Path,
+    endpoint: &str,
+) -> ExitCode {
+    let endpoint = match Endpoint::parse(endpoint) {
+        Ok(endpoint) => endpoint,
+        Err(message) => {
+            eprintln!("{}", message);
+            return ExitCode::FAILURE;
+        }
+    };
+    let ledger = match load_ledger(answers) {
+        Ok(ledger) => ledger,
+        Err(message) => {
+            eprintln!("{}", message);
+            return ExitCode::FAILURE;
+        }
+    };
+    let answer = match answer {
+        Some(answer) => answer,
+        None => match solve(day, part, input_dir) {
+            Ok(answer) => answer.to_string(),
+            Err(message) => {
+                eprintln!("{}", message);
+                return ExitCode::FAILURE;
+            }
+        },
+    };
+
+    // Every wrong answer costs a wait, so don't send one the ledger rules out
+    let candidate = match answer.parse::<i128>() {
+        Ok(n) => Answer::Number(n),
+        Err(_) => Answer::Text(answer.clone()),
+    };
+    match ledger.check(day, part, &candidate) {
+        Check::Unknown => (),
+        Check::Confirmed => {
+            println!(
+                "Day {}, Part {}: {} is already confirmed",
+                day,
+                part.number(),
+                answer
+            );
+            return ExitCode::SUCCESS;
+        }
+        check => {
+            eprintln!(
+                "Not submitting: day {}, part {}: {} {}",
+                day,
+                part.number(),
+                answer,
+                check
+            );
+            return ExitCode::FAILURE;
+        }
+    }
+
+    let session = env::var(submit::SESSION_VARIABLE).ok();
+    match submit::submit(&endpoint, session.as_deref(), day, part, &answer) {
+        Ok(Outcome::Verdict(verdict)) => {
+            println!(
+                "Day {}, Part {}: {} is {}",
+                day,
+                part.number(),
+                answer,
+                verdict
+            );
+            let entry = Entry {
+                day,
+                part,
+                verdict,
+                answer,
+            };
+            let recorded = record(entry, answers);
+            if verdict == Verdict::Correct {
+                recorded
+            } else {
+                ExitCode::FAILURE
+            }
+        }
+        Ok(Outcome::RateLimited { wait }) => {
+            eprintln!(
+                "Answered too recently, try again in {}",
+                wait.as_deref().unwrap_or("a while")
+            );
+            ExitCode::FAILURE
+        }
+        Err(message) => {
+            eprintln!("{}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+// The answer to one part of a day's puzzle input
+fn solve(day: u8, part: Part, input_dir: &Path) -> Result<Answer, String> {
+    let entry = solution::find(day).ok_or_else(|| format!("Day {} is not implemented", day))?;
+    let source = Source::File(input::input_path(input_dir, day));
+    let input = load_input(&source)?;
+    let mut run = entry
+        .solution
+        .run(&input, &[part])
+        .map_err(|e| describe_error(&source, &e))?;
+    run.parts
+        .remove(0)
+        .answer
+        .map_err(|e| format!("Day {}, Part {}: {}", day, part.number(), e))
+}
+
+fn mock_server(port: u16, answers: &Path, cooldown: Duration) -> ExitCode {
+    let ledger = match load_ledger(answers) {
+        Ok(ledger) => ledger,
+        Err(message) => {
+            eprintln!("{}", message);
+            return ExitCode::FAILURE;
+        }
+    };
+    let listener = match TcpListener::bind(("127.0.0.1", port)) {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("Unable to listen on port {}: {}", port, e);
+            return ExitCode::FAILURE;
+        }
+    };
+    // Printed on stdout so that whoever started us can find the port
+    match listener.local_addr() {
+        Ok(addr) => println!("Listening on http://{}", addr),
+        Err(e) => eprintln!("Listening on an unknown port: {}", e),
+    }
+    let _ = io::Write::flush(&mut io::stdout());
+    match Mock::new(&ledger, cooldown).serve(listener) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn new_day(day: u8, root: &Path) -> ExitCode {
+    match scaffold::new_day(root, day) {
+        Ok(changed) => {
+            for path in changed {
+                println!("Wrote {}", path.display());
+            }
+            println!(
+                "Paste the puzzle input and the example into the input files, \
+                 then add the example answers to tests/samples.rs"
+            );
+            ExitCode::SUCCESS
+        }
+        Err(message) => {
+            eprintln!("{}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+// Carries out a parsed command line
+pub fn run(command: Command) -> ExitCode {
+    match command {
+        Command::Run {
+            day,
+            part,
+            input,
+            answers,
+            format,
+            explain,
+        } => run_day(day, part, &input, answers.as_deref(), format, explain),
+        Command::All {
+            input_dir,
+            format,
+            workers,
+        } => run_all(
+            &input_dir,
+            format,
+            workers.unwrap_or_else(pool::default_workers),
+        ),
+        Command::Bench {
+            day,
+            runs,
+            input_dir,
+            history,
+            threshold,
+        } => run_bench(day, runs, &input_dir, &history, threshold),
+        Command::Diff { day, part, input } => run_diff(day, part, &input),
+        Command::Generate { day, size, seed } => run_generate(day, size, seed),
+        Command::Watch {
+            day,
+            input_dir,
+            interval,
+        } => watch(day, &input_dir, interval),
+        Command::Animate {
+            day,
+            part,
+            input,
+            fps,
+        } => animate(day, part, &input, fps),
+        Command::Image {
+            day,
+            input,
+            output_dir,
+            format,
+            scale,
+        } => write_images(day, &input, &output_dir, format, scale),
+        Command::Verify { input_dir, answers } => verify(&input_dir, &answers),
+        Command::Record {
+            day,
+            part,
+            verdict,
+            answer,
+            answers,
+        } => record(
+            Entry {
+                day,
+                part,
+                verdict,
+                answer,
+            },
+            &answers,
+        ),
+        Command::Submit {
+            day,
+            part,
+            answer,
+            input_dir,
+            answers,
+            endpoint,
+        } => submit_answer(day, part, answer, &input_dir, &answers, &endpoint),
+        Command::MockServer {
+            port,
+            answers,
+            cooldown,
+        } => mock_server(port, &answers, cooldown),
+        Command::NewDay { day, root } => new_day(day, &root),
+    }
+}
@@ -1,31 +1,41 @@
 // Use main's lib module here?
-use super::lib::read_file_and_parse_lines;
+use super::generate::Rng;
+use super::lib::{parse_each_line, Part, Res, Result};
+use super::solution::{Alternative, Answer, Solution};
+use nom::{character::complete::u32, error::context};
 use std::clone::Clone;
 
-fn zip_solution(offset: usize, vec: &Vec<u32>) -> u32 {
+pub fn parse_depth(input: &str) -> Res<&str, u32> {
+    context("depth", u32)(input)
+}
+
+// Alternative implementations, kept around for comparison.
+// `aoc-2021 diff --day 1` checks them against part1 and part2.
+pub fn zip_solution(offset: usize, vec: &[u32]) -> u32 {
     if vec.len() < offset + 1 {
         return 0;
     };
     let zipped = vec.iter().zip(vec[offset..].iter());
-    return zipped.fold(0, |acc, (x, y)| if x < y { acc + 1 } else { acc });
+    zipped.fold(0, |acc, (x, y)| if x < y { acc + 1 } else { acc })
 }
 
-fn build_subvectors(size: usize, vec: &Vec<u32>) -> Vec<Vec<u32>> {
+fn build_subvectors(size: usize, vec: &[u32]) -> Vec<Vec<u32>> {
     let mut res: Vec<Vec<u32>> = Vec::new();
     for win in vec.windows(size) {
         res.push(win.to_vec());
     }
-    return res;
+    res
 }
 
 // This one works for offset = 1 but not offset = 3 and I don't care to figure out why :smile:
-fn with_windows(offset: usize, vec: &Vec<u32>) -> u32 {
+// (`aoc-2021 diff --day 1 --part 2` finds the smallest input where it goes wrong)
+pub fn with_windows(offset: usize, vec: &[u32]) -> u32 {
     if vec.len() < offset + 1 {
         return 0;
     };
     let subvectors = build_subvectors(offset, vec);
     let zipped = subvectors.iter().zip(subvectors[offset..].iter());
-    return zipped.fold(0, |acc, (x, y)| {
+    zipped.fold(0, |acc, (x, y)| {
         let x_sum: u32 = x.clone().iter().sum();
         let y_sum: u32 = y.clone().iter().sum();
         if x_sum < y_sum {
@@ -33,19 +43,19 @@ fn with_windows(offset: usize, vec: &Vec<u32>) -> u32 {
         } else {
             acc
         }
-    });
+    })
 }
 
 // part1 :: List Int -> Int
 // part1 (a : b : rest) = (if a < b then 1 else 0) + part1 (b : rest)
 // part1 _ = 0
-fn part1(v: &Vec<u32>) -> u32 {
-    match v.as_slice() {
+pub fn part1(v: &[u32]) -> u32 {
+    match v {
         [x, y, rest @ ..] => {
             let mut next: Vec<u32> = Vec::new();
-            next.push(y.clone());
+            next.push(*y);
             next.extend(rest.to_vec());
-            return (if x < y { 1 } else { 0 }) + part1(&next);
+            (if x < y { 1 } else { 0 }) + part1(&next)
         }
         _ => 0,
     }
@@ -54,39 +64,74 @@ fn part1(v: &Vec<u32>) -> u32 {
 // part2 :: List Int -> Int
 // part2 (a : rest@(_ : _ : d : _)) = (if a < d then 1 else 0) + part2 rest
 // part2 _ = 0
-fn part2(v: &Vec<u32>) -> u32 {
-    match v.as_slice() {
+pub fn part2(v: &[u32]) -> u32 {
+    match v {
         [x, y, z, a, rest @ ..] => {
             let mut next: Vec<u32> = Vec::new();
-            next.push(y.clone());
-            next.push(z.clone());
-            next.push(a.clone());
+            next.push(*y);
+            next.push(*z);
+            next.push(*a);
             next.extend(rest.to_vec());
-            return (if x < a { 1 } else { 0 }) + part2(&next);
+            (if x < a { 1 } else { 0 }) + part2(&next)
         }
         _ => 0,
     }
 }
 
-pub fn run() {
-    let sonar_sweep_depths: Vec<u32> =
-        read_file_and_parse_lines("./inputs/day1.txt", |s| s.parse::<u32>().ok());
-    println!("Part 1: {:?}", part1(&sonar_sweep_depths));
-    println!("Part 2: {:?}", part2(&sonar_sweep_depths));
-    println!(
-        "Part 1 (using zip): {:?}",
-        zip_solution(1, &sonar_sweep_depths)
-    );
-    println!(
-        "Part 2 (using zip): {:?}",
-        zip_solution(3, &sonar_sweep_depths)
-    );
-    println!(
-        "Part 1 (using windows): {:?}",
-        with_windows(1, &sonar_sweep_depths)
-    );
-    println!(
-        "Part 2 (using windows): {:?}",
-        with_windows(3, &sonar_sweep_depths)
-    );
+// Depths wander up and down from somewhere near the surface
+fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut depth = rng.between(100, 200);
+    (0..size.max(1))
+        .map(|_| {
+            depth = (depth + rng.below(41)).saturating_sub(20);
+            format!("{}\n", depth)
+        })
+        .collect()
+}
+
+pub struct Day1;
+
+impl Solution for Day1 {
+    type Input = Vec<u32>;
+
+    fn parse(&self, input: &str) -> Result<Vec<u32>> {
+        parse_each_line(input, parse_depth)
+    }
+
+    fn part1(&self, sonar_sweep_depths: &Vec<u32>) -> Result<Answer> {
+        Ok(part1(sonar_sweep_depths).into())
+    }
+
+    fn part2(&self, sonar_sweep_depths: &Vec<u32>) -> Result<Answer> {
+        Ok(part2(sonar_sweep_depths).into())
+    }
+
+    fn alternatives(&self) -> Vec<Alternative<Vec<u32>>> {
+        vec![
+            Alternative {
+                name: "zip_solution",
+                part: Part::One,
+                solve: |v| Ok(zip_solution(1, v).into()),
+            },
+            Alternative {
+                name: "with_windows",
+                part: Part::One,
+                solve: |v| Ok(with_windows(1, v).into()),
+            },
+            Alternative {
+                name: "zip_solution",
+                part: Part::Two,
+                solve: |v| Ok(zip_solution(3, v).into()),
+            },
+            Alternative {
+                name: "with_windows",
+                part: Part::Two,
+                solve: |v| Ok(with_windows(3, v).into()),
+            },
+        ]
+    }
+
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        Some(generate(rng, size))
+    }
 }
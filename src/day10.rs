@@ -1,38 +1,32 @@
+use super::generate::Rng;
+use super::lib::{self, expected, list, parse_all, Error, Res};
+use super::solution::{Answer, Solution};
+use nom::{bytes::complete::is_a, character::complete::newline, combinator::map, error::context};
+
+pub fn parse_lines(input: &str) -> Res<&str, Vec<String>> {
+    let chunk = expected("a bracket", is_a("()[]{}<>"));
+    list(newline, context("chunk", map(chunk, String::from)))(input)
+}
+
 fn is_opening_char(c: &char) -> bool {
-    match c {
-        '(' => true,
-        '[' => true,
-        '<' => true,
-        '{' => true,
-        _ => false,
-    }
+    matches!(c, '(' | '[' | '<' | '{')
 }
 
 fn check_if_match(x: &char, y: &char) -> bool {
-    match (x, y) {
-        ('(', ')') => true,
-        ('[', ']') => true,
-        ('{', '}') => true,
-        ('<', '>') => true,
-        _ => false,
-    }
+    matches!((x, y), ('(', ')') | ('[', ']') | ('{', '}') | ('<', '>'))
 }
 
-fn process_chunk(input: &str) -> Result<(), char> {
+pub fn process_chunk(input: &str) -> Result<(), char> {
     let mut char_stack: Vec<char> = Vec::new();
     for c in input.chars() {
         if is_opening_char(&c) {
             char_stack.push(c)
         } else {
             match char_stack.last() {
-                None => return Err(c),
-                Some(last_char) => {
-                    if check_if_match(last_char, &c) {
-                        char_stack.pop();
-                    } else {
-                        return Err(c);
-                    }
+                Some(last_char) if check_if_match(last_char, &c) => {
+                    char_stack.pop();
                 }
+                _ => return Err(c),
             }
         }
     }
@@ -49,28 +43,25 @@ fn generate_score(input: Result<(), char>) -> usize {
     }
 }
 
-fn process(input: &Vec<&str>) -> usize {
+pub fn process(input: &[String]) -> usize {
     input
         .iter()
         .map(|x| process_chunk(x))
         .fold(0, |acc, x| acc + generate_score(x))
 }
 
-fn find_stack(input: &str) -> Result<Vec<char>, ()> {
+// The chunks left open, or the first illegal character like process_chunk
+pub fn find_stack(input: &str) -> Result<Vec<char>, char> {
     let mut char_stack: Vec<char> = Vec::new();
     for c in input.chars() {
         if is_opening_char(&c) {
             char_stack.push(c)
         } else {
             match char_stack.last() {
-                None => return Err(()),
-                Some(last_char) => {
-                    if check_if_match(last_char, &c) {
-                        char_stack.pop();
-                    } else {
-                        return Err(());
-                    }
+                Some(last_char) if check_if_match(last_char, &c) => {
+                    char_stack.pop();
                 }
+                _ => return Err(c),
             }
         }
     }
@@ -83,11 +74,12 @@ fn clear_it_out(input: char) -> char {
         '{' => '}',
         '[' => ']',
         '<' => '>',
-        _ => panic!("Got {:?} but expected opening character", input),
+        // The stack only ever holds opening characters
+        _ => unreachable!("Got {:?} but expected opening character", input),
     }
 }
 
-fn complete_stack(input: &Vec<char>) -> Vec<char> {
+pub fn complete_stack(input: &[char]) -> Vec<char> {
     // let mut result: Vec<char> = Vec::new();
     input.iter().rev().map(|x| clear_it_out(*x)).collect()
 }
@@ -98,57 +90,243 @@ fn character_scores_part_2(input: char) -> usize {
         ']' => 2,
         '}' => 3,
         '>' => 4,
-        _ => panic!("Got {:?} but expected opening character", input),
+        // Only ever called on the output of clear_it_out
+        _ => unreachable!("Got {:?} but expected closing character", input),
     }
 }
 
-fn generate_score_part_2(input: Vec<char>) -> usize {
+// Every closing character multiplies the score by 5, so a usize runs out at
+// about 28 of them
+fn generate_score_part_2(input: Vec<char>) -> lib::Result<usize> {
     let mut current_score: usize = 0;
-    for c in input {
-        current_score = current_score * 5 + character_scores_part_2(c);
+    for c in &input {
+        current_score = current_score
+            .checked_mul(5)
+            .and_then(|score| score.checked_add(character_scores_part_2(*c)))
+            .ok_or_else(|| {
+                Error::NoSolution(format!(
+                    "The score for completing {} chunks doesn't fit in a usize",
+                    input.len()
+                ))
+            })?;
     }
-    current_score
+    Ok(current_score)
 }
 
-fn part2(input: &Vec<&str>) -> usize {
+pub fn part2(input: &[String]) -> lib::Result<usize> {
     let mut scores: Vec<usize> = Vec::new();
     for line in input {
         if let Ok(stack) = find_stack(line) {
             let completed_stack = complete_stack(&stack);
-            scores.push(generate_score_part_2(completed_stack));
+            scores.push(generate_score_part_2(completed_stack)?);
         }
     }
     scores.sort();
-    // The number of scores must be odd
-    assert_eq!(scores.len() % 2, 1);
+    // The number of scores must be odd, otherwise there is no middle one
+    if scores.len().is_multiple_of(2) {
+        return Err(Error::NoSolution(format!(
+            "Expected an odd number of incomplete lines, got {}",
+            scores.len()
+        )));
+    }
     let index: usize = scores.len() / 2;
-    scores[index]
+    Ok(scores[index])
+}
+
+// A random walk of opening and closing brackets. A corrupted line then closes
+// a chunk with the wrong bracket, an incomplete one stops with chunks open.
+// Fewer than 28 chunks are ever open, so the completion score fits.
+fn generate_line(rng: &mut Rng, corrupted: bool) -> String {
+    const PAIRS: [(char, char); 4] = [('(', ')'), ('[', ']'), ('{', '}'), ('<', '>')];
+    const MAX_OPEN: usize = 27;
+    let mut line = String::new();
+    let mut open: Vec<char> = Vec::new();
+    for _ in 0..rng.between(4, 40) {
+        if open.is_empty() || (open.len() < MAX_OPEN && rng.chance(55)) {
+            let (opening, closing) = *rng.choose(&PAIRS);
+            line.push(opening);
+            open.push(closing);
+        } else if let Some(closing) = open.pop() {
+            line.push(closing);
+        }
+    }
+    if open.is_empty() {
+        let (opening, closing) = *rng.choose(&PAIRS);
+        line.push(opening);
+        open.push(closing);
+    }
+    if corrupted {
+        let expected = open[open.len() - 1];
+        let wrong: Vec<char> = PAIRS
+            .iter()
+            .map(|(_, closing)| *closing)
+            .filter(|closing| *closing != expected)
+            .collect();
+        line.push(*rng.choose(&wrong));
+    }
+    line
 }
 
-pub fn run() {
-    let chunk = "([])";
-    assert_eq!(process_chunk(&chunk), Ok(()));
+// part2 needs an odd number of incomplete lines to have a middle score
+fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut corrupted: Vec<bool> = (0..size.max(1)).map(|_| rng.chance(50)).collect();
+    if corrupted.iter().filter(|c| !**c).count().is_multiple_of(2) {
+        corrupted[0] = !corrupted[0];
+    }
+    corrupted
+        .into_iter()
+        .map(|corrupted| generate_line(rng, corrupted) + "\n")
+        .collect()
+}
+
+pub struct Day10;
 
-    let chunk = "[<>({}){}[([])<>]]";
-    assert_eq!(process_chunk(&chunk), Ok(()));
+impl Solution for Day10 {
+    type Input = Vec<String>;
 
-    let chunk = "{()()()>";
-    assert_eq!(process_chunk(&chunk), Err('>'));
+    fn parse(&self, input: &str) -> lib::Result<Vec<String>> {
+        parse_all(input, parse_lines)
+    }
+
+    fn part1(&self, input: &Vec<String>) -> lib::Result<Answer> {
+        Ok(process(input).into())
+    }
 
-    let chunk = "<([]){()}[{}])";
-    assert_eq!(process_chunk(&chunk), Err(')'));
+    fn part2(&self, input: &Vec<String>) -> lib::Result<Answer> {
+        part2(input).map(Answer::from)
+    }
 
-    let chunk = "{([(<{}[<>[]}>{[]{[(<()>";
-    assert_eq!(process_chunk(&chunk), Err('}'));
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        Some(generate(rng, size))
+    }
+}
 
-    let chunk = "<{([([[(<>()){}]>(<<{{";
-    assert_eq!(process_chunk(&chunk), Err('>'));
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::property::assert_property;
 
-    let test_str: Vec<&str> = include_str!("../inputs/day10.test").lines().collect();
-    assert_eq!(process(&test_str), 26397);
+    // Opens and closes chunks at random until every chunk is closed
+    fn balanced(rng: &mut Rng, size: usize) -> String {
+        let mut line = String::new();
+        let mut open: Vec<char> = Vec::new();
+        for _ in 0..size {
+            if open.is_empty() || rng.chance(50) {
+                let opening = *rng.choose(&['(', '[', '{', '<']);
+                line.push(opening);
+                open.push(clear_it_out(opening));
+            } else if let Some(closing) = open.pop() {
+                line.push(closing);
+            }
+        }
+        line.extend(open.iter().rev());
+        line
+    }
 
-    let input_str: Vec<&str> = include_str!("../inputs/day10").lines().collect();
-    assert_eq!(process(&input_str), 318081);
-    assert_eq!(part2(&test_str), 288957);
-    println!("{:?}", part2(&input_str));
+    // Removing a chunk's opening and closing brackets keeps the line balanced
+    fn shrink_balanced(line: &str) -> Vec<String> {
+        let chars: Vec<char> = line.chars().collect();
+        let mut candidates: Vec<String> = Vec::new();
+        let mut open: Vec<usize> = Vec::new();
+        for (idx, c) in chars.iter().enumerate() {
+            if is_opening_char(c) {
+                open.push(idx);
+            } else if let Some(start) = open.pop() {
+                candidates.push(
+                    chars
+                        .iter()
+                        .enumerate()
+                        .filter(|(i, _)| *i != start && *i != idx)
+                        .map(|(_, c)| *c)
+                        .collect(),
+                );
+            }
+        }
+        candidates
+    }
+
+    #[test]
+    fn accepts_every_balanced_line() {
+        assert_property(
+            "process_chunk accepts every balanced line",
+            balanced,
+            |line| shrink_balanced(line),
+            |line| match process_chunk(line) {
+                Ok(()) => Ok(()),
+                Err(c) => Err(format!("rejected {:?}", c)),
+            },
+        );
+    }
+
+    #[test]
+    fn finds_the_first_illegal_character() {
+        let chunk = "([])";
+        assert_eq!(process_chunk(chunk), Ok(()));
+
+        let chunk = "[<>({}){}[([])<>]]";
+        assert_eq!(process_chunk(chunk), Ok(()));
+
+        let chunk = "{()()()>";
+        assert_eq!(process_chunk(chunk), Err('>'));
+
+        let chunk = "<([]){()}[{}])";
+        assert_eq!(process_chunk(chunk), Err(')'));
+
+        let chunk = "{([(<{}[<>[]}>{[]{[(<()>";
+        assert_eq!(process_chunk(chunk), Err('}'));
+
+        let chunk = "<{([([[(<>()){}]>(<<{{";
+        assert_eq!(process_chunk(chunk), Err('>'));
+    }
+
+    #[test]
+    fn scores_the_sample() {
+        let test_str: Vec<String> = include_str!("../inputs/day10.test")
+            .lines()
+            .map(String::from)
+            .collect();
+        assert_eq!(process(&test_str), 26397);
+
+        assert_eq!(part2(&test_str).ok(), Some(288957));
+    }
+
+    #[test]
+    fn completes_incomplete_lines() {
+        assert_eq!(
+            complete_stack(&find_stack("[({(<(())[]>[[{[]{<()<>>").unwrap()),
+            "}}]])})]".chars().collect::<Vec<char>>()
+        );
+        assert_eq!(
+            generate_score_part_2("}}]])})]".chars().collect()).ok(),
+            Some(288957)
+        );
+    }
+
+    #[test]
+    fn too_many_open_chunks_have_no_score() {
+        assert!(part2(&["(".repeat(27)]).is_ok());
+        // Too big for an i64, but the answer keeps every digit
+        assert_eq!(
+            Day10.part2(&vec!["(".repeat(28)]).ok(),
+            Some(Answer::Number(9313225746154785156))
+        );
+        assert!(part2(&["(".repeat(29)]).is_err());
+        assert!(matches!(
+            part2(&["(".repeat(30)]),
+            Err(Error::NoSolution(_))
+        ));
+    }
+
+    #[test]
+    fn generated_lines_have_a_score() {
+        let input = generate(&mut Rng::new(10), 2000);
+        let lines = parse_all(&input, parse_lines).unwrap();
+        assert!(part2(&lines).is_ok());
+    }
+
+    #[test]
+    fn needs_an_odd_number_of_incomplete_lines() {
+        let input = vec!["((".to_string(), "[".to_string()];
+        assert!(matches!(part2(&input), Err(Error::NoSolution(_))));
+    }
 }
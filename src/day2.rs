@@ -1,44 +1,26 @@
-use super::lib::{Monoid, Semigroup};
+use super::generate::Rng;
+use super::lib::{expected, par_mconcat, parse_each_line, Error, Monoid, Res, Result, Semigroup};
+use super::solution::{Answer, Solution};
 use nom::{
     branch::alt,
     bytes::complete::tag,
-    character::complete::digit1,
+    character::complete::{char, digit1},
     combinator::{map_res, recognize},
-    error::{context, VerboseError},
-    IResult,
+    error::context,
 };
 use std::fmt;
 
-// A wrapper around IResult for our custom parser
-type Res<T, U> = IResult<T, U, VerboseError<T>>;
-
 // Parse a number like 42
 fn parse_digit(input: &str) -> Res<&str, i64> {
     map_res(recognize(digit1), str::parse)(input)
 }
 
-#[derive(Debug)]
-struct Position {
-    horizontal: i64,
-    depth: i64,
-}
-
-impl Semigroup for Position {
-    fn mappend(fst: Position, snd: Position) -> Position {
-        Position {
-            horizontal: fst.horizontal + snd.horizontal,
-            depth: fst.depth + snd.depth,
-        }
-    }
-}
-
-impl Monoid for Position {
-    fn mempty() -> Position {
-        Position {
-            horizontal: 0,
-            depth: 0,
-        }
-    }
+#[derive(Debug, Clone, PartialEq, Semigroup, Monoid)]
+pub struct Position {
+    #[monoid(sum)]
+    pub horizontal: i64,
+    #[monoid(sum)]
+    pub depth: i64,
 }
 
 impl fmt::Display for Position {
@@ -59,13 +41,18 @@ enum Movement {
     Down,
 }
 
-impl From<&str> for Movement {
-    fn from(i: &str) -> Self {
-        match i.to_owned().as_str() {
-            "forward" => Movement::Forward,
-            "up" => Movement::Up,
-            "down" => Movement::Down,
-            _ => unimplemented!("The only acceptable movements are: forward, up, or down"),
+impl TryFrom<&str> for Movement {
+    type Error = Error;
+
+    fn try_from(i: &str) -> Result<Self> {
+        match i {
+            "forward" => Ok(Movement::Forward),
+            "up" => Ok(Movement::Up),
+            "down" => Ok(Movement::Down),
+            _ => Err(Error::Validation(format!(
+                "The only acceptable movements are: forward, up, or down, got: {:?}",
+                i
+            ))),
         }
     }
 }
@@ -74,14 +61,19 @@ fn parse_movement(input: &str) -> Res<&str, Movement> {
     context(
         "movement",
         // Can use tag_no_case for case insensitive match
-        alt((tag("forward"), tag("up"), tag("down"))),
+        map_res(
+            expected(
+                "forward|up|down",
+                alt((tag("forward"), tag("up"), tag("down"))),
+            ),
+            Movement::try_from,
+        ),
     )(input)
-    .map(|(next_input, res)| (next_input, res.into()))
 }
 
-fn position_parser(input: &str) -> Res<&str, Position> {
+pub fn position_parser(input: &str) -> Res<&str, Position> {
     let (input, movement) = parse_movement(input)?;
-    let (input, _) = tag(" ")(input)?;
+    let (input, _) = char(' ')(input)?;
     let (input, value) = parse_digit(input)?;
     match movement {
         Movement::Forward => Ok((
@@ -108,9 +100,9 @@ fn position_parser(input: &str) -> Res<&str, Position> {
     }
 }
 
-fn aim_parser(input: &str) -> Res<&str, Aim> {
+pub fn aim_parser(input: &str) -> Res<&str, Aim> {
     let (input, movement) = parse_movement(input)?;
-    let (input, _) = tag(" ")(input)?;
+    let (input, _) = char(' ')(input)?;
     let (input, value) = parse_digit(input)?;
     match movement {
         Movement::Forward => Ok((
@@ -118,7 +110,7 @@ fn aim_parser(input: &str) -> Res<&str, Aim> {
             Aim {
                 horizontal: value,
                 depth: 0,
-                aim: value,
+                aim: 0,
             },
         )),
         Movement::Up => Ok((
@@ -140,28 +132,21 @@ fn aim_parser(input: &str) -> Res<&str, Aim> {
     }
 }
 
-#[derive(Debug)]
-struct Aim {
-    horizontal: i64,
-    depth: i64,
-    aim: i64,
+#[derive(Debug, Clone, PartialEq)]
+pub struct Aim {
+    pub horizontal: i64,
+    pub depth: i64,
+    pub aim: i64,
 }
 
+// snd's moves all happen after fst's, so its forward moves dive at fst's aim on
+// top of its own
 impl Semigroup for Aim {
     fn mappend(fst: Aim, snd: Aim) -> Aim {
-        let is_horizontal = snd.horizontal > 0;
         Aim {
             horizontal: fst.horizontal + snd.horizontal,
-            depth: if is_horizontal {
-                fst.depth + fst.aim * snd.horizontal
-            } else {
-                fst.depth
-            },
-            aim: if is_horizontal {
-                fst.aim
-            } else {
-                fst.aim + snd.aim
-            },
+            depth: fst.depth + snd.depth + fst.aim * snd.horizontal,
+            aim: fst.aim + snd.aim,
         }
     }
 }
@@ -186,46 +171,78 @@ impl fmt::Display for Aim {
     }
 }
 
-fn part1() -> Position {
-    let lines: Vec<&str> = include_str!("../inputs/day2.txt")
-        .trim_end()
-        .lines()
-        .collect();
-    lines
-        .iter()
-        .map(|s| {
-            // This unwrap isn't great but we know it should succeed
-            let (_, pos) = position_parser(s).unwrap();
-            pos
+pub struct Course {
+    pub positions: Vec<Position>,
+    pub aims: Vec<Aim>,
+}
+
+pub fn part1(input: &Course) -> Position {
+    par_mconcat(input.positions.iter().cloned())
+}
+
+pub fn part2(input: &Course) -> Aim {
+    par_mconcat(input.aims.iter().cloned())
+}
+
+fn generate(rng: &mut Rng, size: usize) -> String {
+    (0..size.max(1))
+        .map(|_| {
+            let movement = rng.choose(&["forward", "up", "down"]);
+            format!("{} {}\n", movement, rng.between(1, 9))
         })
-        .fold(Monoid::mempty(), |x, y| Semigroup::mappend(x, y))
-}
-
-fn part2() -> Aim {
-    let lines: Vec<&str> = include_str!("../inputs/day2.txt")
-        .trim_end()
-        .lines()
-        .collect();
-    lines
-        .iter()
-        .map(|s| {
-            // This unwrap isn't great but we know it should succeed
-            let (_, pos) = aim_parser(s).unwrap();
-            pos
+        .collect()
+}
+
+pub struct Day2;
+
+impl Solution for Day2 {
+    type Input = Course;
+
+    fn parse(&self, input: &str) -> Result<Course> {
+        Ok(Course {
+            positions: parse_each_line(input, position_parser)?,
+            aims: parse_each_line(input, aim_parser)?,
         })
-        .fold(Monoid::mempty(), |x, y| Semigroup::mappend(x, y))
-}
-
-pub fn run() {
-    let Position {
-        horizontal: h_one,
-        depth: d_one,
-    } = part1();
-    let Aim {
-        horizontal: h_two,
-        depth: d_two,
-        aim: _,
-    } = part2();
-    println!("Day 2, Part 1: {}", h_one * d_one);
-    println!("Day 2, Part 2: {}", h_two * d_two);
+    }
+
+    fn part1(&self, input: &Course) -> Result<Answer> {
+        let Position { horizontal, depth } = part1(input);
+        Ok((horizontal * depth).into())
+    }
+
+    fn part2(&self, input: &Course) -> Result<Answer> {
+        let Aim {
+            horizontal,
+            depth,
+            aim: _,
+        } = part2(input);
+        Ok((horizontal * depth).into())
+    }
+
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        Some(generate(rng, size))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::laws::{assert_laws, signed};
+
+    #[test]
+    fn position_is_a_monoid() {
+        assert_laws("Position", |rng, size| Position {
+            horizontal: signed(rng, size),
+            depth: signed(rng, size),
+        });
+    }
+
+    #[test]
+    fn aim_is_a_monoid() {
+        assert_laws("Aim", |rng, size| Aim {
+            horizontal: signed(rng, size),
+            depth: signed(rng, size),
+            aim: signed(rng, size),
+        });
+    }
 }
@@ -1,25 +1,27 @@
-use super::lib::{Monoid, Semigroup, Res};
-use nom::{
-    branch::alt,
-    bytes::complete::tag,
-    error::context,
-    multi::many1,
-};
+use super::generate::Rng;
+use super::lib::{expected, par_fold_map, parse_each_line, Error, Monoid, Res, Result, Semigroup};
+use super::solution::{Answer, Solution};
+use super::trace::{self, Event};
+use nom::{branch::alt, bytes::complete::tag, combinator::map_res, error::context, multi::many1};
 use std::cmp::PartialEq;
-use std::iter;
 
 #[derive(Debug, Clone, PartialEq)]
-enum Bit {
+pub enum Bit {
     Zero,
     One,
 }
 
-impl From<&str> for Bit {
-    fn from(i: &str) -> Self {
-        match i.to_owned().as_str() {
-            "0" => Bit::Zero,
-            "1" => Bit::One,
-            _ => unimplemented!("The only acceptable bits are: 0, 1"),
+impl TryFrom<&str> for Bit {
+    type Error = Error;
+
+    fn try_from(i: &str) -> Result<Self> {
+        match i {
+            "0" => Ok(Bit::Zero),
+            "1" => Ok(Bit::One),
+            _ => Err(Error::Validation(format!(
+                "The only acceptable bits are: 0, 1, got: {:?}",
+                i
+            ))),
         }
     }
 }
@@ -28,48 +30,49 @@ fn parse_bit(input: &str) -> Res<&str, Bit> {
     context(
         "bit",
         // Can use tag_no_case for case insensitive match
-        alt((tag("0"), tag("1"))),
+        map_res(expected("0|1", alt((tag("0"), tag("1")))), Bit::try_from),
     )(input)
-    .map(|(next_input, res)| (next_input, res.into()))
 }
 
 // We need to parse one or more u8's
 // We are given a variable number in the input
-fn parse_line(input: &str) -> Res<&str, Vec<Bit>> {
+pub fn parse_line(input: &str) -> Res<&str, Vec<Bit>> {
     let (input, result) = (many1(parse_bit))(input)?;
     Ok((input, result))
 }
 
-fn build_bit_2d_vec(inp: &Vec<&str>) -> Vec<Vec<Bit>> {
-    inp.iter()
-        .map(|s| {
-            let (_, vec) = parse_line(s).unwrap();
-            vec
-        })
-        .collect()
+// Every report needs the same number of bits. Both answers multiply two of
+// the numbers in a u32, so they can have at most 16.
+pub fn validate(inp: &[Vec<Bit>]) -> Result<()> {
+    let number_of_bits = match inp.first() {
+        Some(first) => first.len(),
+        None => return Err(Error::Validation("The report is empty".to_string())),
+    };
+    if number_of_bits > 16 {
+        return Err(Error::Validation(format!(
+            "Expected at most 16 bits per number, got: {}",
+            number_of_bits
+        )));
+    }
+    match inp.iter().position(|x| x.len() != number_of_bits) {
+        Some(idx) => Err(Error::Validation(format!(
+            "Expected {} bits on every line, but line {} has {}",
+            number_of_bits,
+            idx + 1,
+            inp[idx].len()
+        ))),
+        None => Ok(()),
+    }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Semigroup, Monoid)]
 struct Count {
+    #[monoid(sum)]
     zeros: u32,
+    #[monoid(sum)]
     ones: u32,
 }
 
-impl Semigroup for Count {
-    fn mappend(fst: Count, snd: Count) -> Count {
-        Count {
-            zeros: fst.zeros + snd.zeros,
-            ones: fst.ones + snd.ones,
-        }
-    }
-}
-
-impl Monoid for Count {
-    fn mempty() -> Count {
-        Count { zeros: 0, ones: 0 }
-    }
-}
-
 fn flip_bit(bit: &Bit) -> Bit {
     match bit {
         Bit::Zero => Bit::One,
@@ -91,7 +94,7 @@ struct Rate {
     epsilon: Vec<Bit>,
 }
 
-fn convert_to_number(inp: &Vec<u8>) -> u32 {
+fn convert_to_number(inp: &[u8]) -> u32 {
     let string = inp.iter().fold(String::new(), |mut acc, num| {
         acc.push_str(&num.to_string());
         acc
@@ -113,19 +116,11 @@ fn build_rates(counts: Vec<Count>) -> Rate {
     }
 }
 
-fn part1(inp: Vec<Vec<Bit>>) -> u32 {
+pub fn part1(inp: &[Vec<Bit>]) -> u32 {
     let number_of_bits = inp[0].len();
-    let mut counts: Vec<Count> = iter::repeat(Monoid::mempty())
-        .take(number_of_bits)
+    let counts: Vec<Count> = (0..number_of_bits)
+        .map(|position| count_at(inp, position))
         .collect();
-    for outer in inp.iter() {
-        for (idx, value) in outer.iter().enumerate() {
-            match value {
-                Bit::Zero => counts[idx].zeros += 1,
-                Bit::One => counts[idx].ones += 1,
-            }
-        }
-    }
     // Get the rates
     let Rate { gamma, epsilon } = build_rates(counts);
 
@@ -137,12 +132,11 @@ fn part1(inp: Vec<Vec<Bit>>) -> u32 {
     gamma_num * epsilon_num
 }
 
-fn count_at(inp: &Vec<Vec<Bit>>, position: usize) -> Count {
-    inp.iter()
-        .fold(Monoid::mempty(), |acc, xs| match xs[position] {
-            Bit::Zero => Semigroup::mappend(acc, Count { zeros: 1, ones: 0 }),
-            Bit::One => Semigroup::mappend(acc, Count { zeros: 0, ones: 1 }),
-        })
+fn count_at(inp: &[Vec<Bit>], position: usize) -> Count {
+    par_fold_map(inp, |xs| match xs[position] {
+        Bit::Zero => Count { zeros: 1, ones: 0 },
+        Bit::One => Count { zeros: 0, ones: 1 },
+    })
 }
 
 fn convert_bit_vec_to_u32(inp: Vec<Bit>) -> u32 {
@@ -156,15 +150,35 @@ fn convert_bit_vec_to_u32(inp: Vec<Bit>) -> u32 {
     convert_to_number(&vec)
 }
 
-fn whittle(inp: &Vec<Vec<Bit>>, position: usize, keeper: impl Fn(Count) -> Bit) -> Vec<Vec<Bit>> {
+fn whittle(inp: &[Vec<Bit>], position: usize, keeper: impl Fn(Count) -> Bit) -> Vec<Vec<Bit>> {
     let mut vec: Vec<Vec<Bit>> = Vec::new();
     let count = count_at(inp, position);
+    let (zeros, ones) = (count.zeros, count.ones);
     let keep = keeper(count);
     for outer in inp.iter() {
         if outer[position] == keep {
             vec.push(outer.to_vec());
         }
     }
+    trace::emit(|| {
+        let kept = if keep == Bit::One { 1 } else { 0 };
+        Event::new(
+            "whittle",
+            format!(
+                "Bit {}: {} zeros and {} ones, keeping the {}s leaves {}",
+                position + 1,
+                zeros,
+                ones,
+                kept,
+                vec.len()
+            ),
+        )
+        .with("position", position)
+        .with("zeros", zeros)
+        .with("ones", ones)
+        .with("kept", kept as usize)
+        .with("candidates", vec.len())
+    });
     vec
 }
 
@@ -181,30 +195,118 @@ fn co2_keep(count: Count) -> Bit {
     flip_bit(&oxygen_keep(count))
 }
 
-fn gen_rating(inp: &Vec<Vec<Bit>>, keeper: &impl Fn(Count) -> Bit) -> u32 {
+fn gen_rating(name: &'static str, inp: &[Vec<Bit>], keeper: &impl Fn(Count) -> Bit) -> Result<u32> {
     let number_of_bits = inp[0].len();
-    let mut accumulator: Vec<Vec<Bit>> = whittle(inp, 0, &keeper);
+    trace::emit(|| {
+        Event::new(
+            "rating",
+            format!(
+                "Whittling {} numbers down to the {} rating",
+                inp.len(),
+                name
+            ),
+        )
+        .with("rating", name)
+    });
+    let mut accumulator: Vec<Vec<Bit>> = whittle(inp, 0, keeper);
     for idx in 1..number_of_bits {
         if accumulator.len() == 1 {
             break;
         }
-        accumulator = whittle(&accumulator, idx, &keeper);
+        accumulator = whittle(&accumulator, idx, keeper);
+    }
+    // Duplicate numbers can whittle the candidates down to nothing
+    match accumulator.first() {
+        Some(rating) => {
+            let value = convert_bit_vec_to_u32(rating.clone());
+            trace::emit(|| {
+                Event::new("result", format!("The {} rating is {}", name, value))
+                    .with("rating", name)
+                    .with("value", value)
+            });
+            Ok(value)
+        }
+        None => Err(Error::NoSolution(
+            "Every candidate was whittled away".to_string(),
+        )),
     }
-    convert_bit_vec_to_u32(accumulator[0].clone())
 }
 
-fn part2(inp: Vec<Vec<Bit>>) -> u32 {
-    let oxygen_generator_rating = gen_rating(&inp, &oxygen_keep);
-    let co2_scrubber_rating = gen_rating(&inp, &co2_keep);
-    oxygen_generator_rating * co2_scrubber_rating
+pub fn part2(inp: &[Vec<Bit>]) -> Result<u32> {
+    let oxygen_generator_rating = gen_rating("oxygen generator", inp, &oxygen_keep)?;
+    let co2_scrubber_rating = gen_rating("CO2 scrubber", inp, &co2_keep)?;
+    Ok(oxygen_generator_rating * co2_scrubber_rating)
 }
 
-pub fn run() {
-    // let day3_test: Vec<&str> = include_str!("../inputs/day3.test.txt").trim_end().lines().collect();
-    let day3_input: Vec<&str> = include_str!("../inputs/day3.txt")
-        .trim_end()
-        .lines()
-        .collect();
-    println!("Day 3, Part 1 {:?}", part1(build_bit_2d_vec(&day3_input)));
-    println!("Day 3, Part 2 {:?}", part2(build_bit_2d_vec(&day3_input)));
+// Whittling a bit that every remaining number shares leaves no candidates, so
+// the numbers are built as a trie that always splits: any group of two or more
+// numbers with the same leading bits has both a 0 and a 1 at the next bit
+fn split(rng: &mut Rng, prefix: u32, bits: u32, count: usize, numbers: &mut Vec<u32>) {
+    if count == 1 {
+        numbers.push(prefix << bits | rng.below(1 << bits) as u32);
+        return;
+    }
+    let half = 1 << (bits - 1);
+    let zeros = rng.between(count.saturating_sub(half).max(1), (count - 1).min(half));
+    split(rng, prefix << 1, bits - 1, zeros, numbers);
+    split(rng, prefix << 1 | 1, bits - 1, count - zeros, numbers);
+}
+
+// gen_rating whittles once before checking for a single candidate, so a lone
+// number never survives. validate allows at most 16 bits.
+fn generate(rng: &mut Rng, size: usize) -> String {
+    let count = size.clamp(2, 1 << 16);
+    let bits = (usize::BITS - (count - 1).leading_zeros() + 2).clamp(5, 16);
+    let mut numbers: Vec<u32> = Vec::new();
+    split(rng, 0, bits, count, &mut numbers);
+    rng.shuffle(&mut numbers);
+    numbers
+        .iter()
+        .map(|n| format!("{:0width$b}\n", n, width = bits as usize))
+        .collect()
+}
+
+pub struct Day3;
+
+impl Solution for Day3 {
+    type Input = Vec<Vec<Bit>>;
+
+    fn parse(&self, input: &str) -> Result<Vec<Vec<Bit>>> {
+        let bits = parse_each_line(input, parse_line)?;
+        validate(&bits)?;
+        Ok(bits)
+    }
+
+    fn part1(&self, input: &Vec<Vec<Bit>>) -> Result<Answer> {
+        Ok(part1(input).into())
+    }
+
+    fn part2(&self, input: &Vec<Vec<Bit>>) -> Result<Answer> {
+        Ok(part2(input)?.into())
+    }
+
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        Some(generate(rng, size))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::laws::assert_laws;
+
+    #[test]
+    fn rejects_numbers_too_long_to_multiply() {
+        let report = "11111111111111111111\n00000000000000000001\n10000000000000000000\n";
+        assert!(matches!(Day3.parse(report), Err(Error::Validation(_))));
+        assert!(Day3.parse("1111111111111111\n0000000000000001\n").is_ok());
+    }
+
+    #[test]
+    fn count_is_a_monoid() {
+        assert_laws("Count", |rng, size| Count {
+            zeros: rng.below(size) as u32,
+            ones: rng.below(size) as u32,
+        });
+    }
 }
@@ -1,51 +1,56 @@
-use super::lib::Res;
+use super::generate::Rng;
+use super::image::{Palette, Picture};
+use super::lib::{expected, list, parse_all, Error, Res, Result};
+use super::solution::{Answer, Solution};
+use super::trace::{self, Event, Grid};
 use nom::{
     character::complete::{char, newline, space0, space1, u16},
     combinator::opt,
-    multi::separated_list1,
+    error::context,
     sequence::{pair, tuple},
 };
 
 pub fn parse_marks(input: &str) -> Res<&str, Vec<u16>> {
-    let (input, result) = separated_list1(char(','), u16)(input)?;
+    let (input, result) = context("marks", list(char(','), u16))(input)?;
     Ok((input, result))
 }
 
 pub fn parse_board_line(input: &str) -> Res<&str, Vec<u16>> {
-    let (input, result) = separated_list1(space1, u16)(input)?;
+    let (input, result) = list(space1, u16)(input)?;
     Ok((input, result))
 }
 
 fn parse_board(input: &str) -> Res<&str, Vec<Vec<u16>>> {
-    let (input, result) = separated_list1(pair(newline, opt(space0)), parse_board_line)(input)?;
+    let (input, result) =
+        context("board", list(pair(newline, opt(space0)), parse_board_line))(input)?;
     Ok((input, result))
 }
 
 fn parse_boards(input: &str) -> Res<&str, Vec<Vec<Vec<u16>>>> {
-    let (input, result) =
-        separated_list1(tuple((newline, newline, opt(space0))), parse_board)(input)?;
+    let (input, result) = list(tuple((newline, newline, opt(space0))), parse_board)(input)?;
     Ok((input, result))
 }
 
 #[derive(Debug, Clone)]
-struct Value {
-    value: u16,
-    marked: bool,
+pub struct Value {
+    pub value: u16,
+    pub marked: bool,
 }
 
-type Board = Vec<Vec<Value>>;
+pub type Board = Vec<Vec<Value>>;
 
-type Boards = Vec<Vec<Vec<Value>>>;
+pub type Boards = Vec<Vec<Vec<Value>>>;
 
 #[derive(Debug, Clone)]
-struct Input {
-    markers: Vec<u16>,
-    boards: Boards,
+pub struct Input {
+    pub markers: Vec<u16>,
+    pub boards: Boards,
 }
 
-fn parse_input(input: &str) -> Res<&str, Input> {
+pub fn parse_input(input: &str) -> Res<&str, Input> {
     let (input, markers) = parse_marks(input)?;
-    let (input, _) = pair(newline, newline)(input)?;
+    // The first row of the first board can be indented too, like every other row
+    let (input, _) = expected("a blank line", tuple((newline, newline, space0)))(input)?;
     let (input, boards) = parse_boards(input)?;
     let unmarked_board = boards
         .iter()
@@ -72,7 +77,20 @@ fn parse_input(input: &str) -> Res<&str, Input> {
     ))
 }
 
-fn mark_board(number_to_mark: u16, board: &Board) -> Board {
+// is_board_winner reads the columns by row index, so boards must be square
+pub fn validate(input: &Input) -> Result<()> {
+    for (idx, board) in input.boards.iter().enumerate() {
+        if board.iter().any(|row| row.len() != board.len()) {
+            return Err(Error::Validation(format!(
+                "Board {} is not square",
+                idx + 1
+            )));
+        }
+    }
+    Ok(())
+}
+
+pub fn mark_board(number_to_mark: u16, board: &Board) -> Board {
     board
         .iter()
         .map(|x| {
@@ -92,80 +110,146 @@ fn mark_board(number_to_mark: u16, board: &Board) -> Board {
         .collect()
 }
 
-fn is_board_winner(board: &Board) -> bool {
+pub fn is_board_winner(board: &Board) -> bool {
     let row_win: bool = board
         .iter()
-        .map(|x| x.iter().all(|Value { value: _, marked }| marked == &true))
-        .any(|x| x == true);
+        .any(|x| x.iter().all(|Value { value: _, marked }| *marked));
 
     let mut column_res: Vec<bool> = Vec::new();
     for column_idx in 0..board.len() {
         let mut tmp: Vec<bool> = vec![];
-        for row_idx in 0..board.len() {
-            tmp.push(board[row_idx][column_idx].marked);
+        for row in board {
+            tmp.push(row[column_idx].marked);
         }
-        column_res.push(tmp.iter().all(|x| x == &true));
+        column_res.push(tmp.iter().all(|x| *x));
     }
-    row_win || column_res.iter().any(|x| x == &true)
+    row_win || column_res.iter().any(|x| *x)
 }
 
 #[derive(Debug)]
-struct Result {
-    board: Board,
-    number: u16,
+pub struct Winner {
+    pub board: Board,
+    pub number: u16,
+}
+
+fn trace_win(board_idx: usize, draw_idx: usize, number: u16, board: &Board) {
+    trace::emit(|| {
+        let unmarked = sum_unmarked_values(board);
+        Event::new(
+            "win",
+            format!(
+                "Board {} wins on draw {} ({}), with {} left unmarked for a score of {}",
+                board_idx + 1,
+                draw_idx + 1,
+                number,
+                unmarked,
+                unmarked * number as u32
+            ),
+        )
+        .with("board", board_idx + 1)
+        .with("draw", draw_idx + 1)
+        .with("number", number)
+        .with("unmarked", unmarked)
+    });
+}
+
+// Every board side by side, with 0 for unmarked numbers, 1 for marked ones and
+// 2 for the marked numbers of boards that have won
+pub fn board_grid(boards: &Boards) -> Grid {
+    let grids: Vec<Grid> = boards
+        .iter()
+        .map(|board| {
+            let won = is_board_winner(board) as i64;
+            board
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .map(|v| Some(v.marked as i64 * (1 + won)))
+                        .collect()
+                })
+                .collect()
+        })
+        .collect();
+    trace::tile(&grids)
+}
+
+fn trace_draw(draw_idx: usize, number: u16, boards: &Boards) {
+    trace::emit(|| {
+        Event::new("draw", format!("Draw {} is {}", draw_idx + 1, number))
+            .with("draw", draw_idx + 1)
+            .with("number", number)
+            .with("boards", board_grid(boards))
+    });
 }
 
-fn part1(input: &Input) -> Option<Result> {
+pub fn part1(input: &Input) -> Option<Winner> {
     // Temporary board while marking
     let mut marked_boards: Boards = input.boards.clone();
 
-    for number_to_mark in &input.markers {
-        for board_idx in 0..marked_boards.len() {
-            // Mark board at board index with number_to_mark
-            marked_boards[board_idx] = mark_board(*number_to_mark, &marked_boards[board_idx]);
-
-            // Check if board is a winner, and return it
-            if is_board_winner(&marked_boards[board_idx]) {
-                return Some(Result {
-                    board: marked_boards[board_idx].clone(),
-                    number: *number_to_mark,
-                });
-            }
+    for (draw_idx, number_to_mark) in input.markers.iter().enumerate() {
+        // Mark every board with number_to_mark
+        for board in marked_boards.iter_mut() {
+            *board = mark_board(*number_to_mark, board);
+        }
+        trace_draw(draw_idx, *number_to_mark, &marked_boards);
+
+        // The first board that is a winner is returned
+        if let Some((board_idx, board)) = marked_boards
+            .iter()
+            .enumerate()
+            .find(|(_, board)| is_board_winner(board))
+        {
+            trace_win(board_idx, draw_idx, *number_to_mark, board);
+            return Some(Winner {
+                board: board.clone(),
+                number: *number_to_mark,
+            });
         }
     }
 
     None
 }
 
-fn part2(input: &Input) -> Result {
+pub fn part2(input: &Input) -> Option<Winner> {
+    play(input).1
+}
+
+// Draws every number, marking each board until it wins. Returns the boards as
+// they were left, and the last board to win.
+pub fn play(input: &Input) -> (Boards, Option<Winner>) {
     // Temporary board while marking
     let mut marked_boards: Boards = input.boards.clone();
-    let mut losing_result: Result = Result {
-        board: Vec::new(),
-        number: 0,
-    };
+    let mut losing_result: Option<Winner> = None;
 
-    for number_to_mark in &input.markers {
-        for board_idx in 0..marked_boards.len() {
+    for (draw_idx, number_to_mark) in input.markers.iter().enumerate() {
+        let mut winners: Vec<usize> = Vec::new();
+        for (board_idx, board) in marked_boards.iter_mut().enumerate() {
             // If the board is already a winner skip it
-            if is_board_winner(&marked_boards[board_idx]) {
+            if is_board_winner(board) {
                 continue;
             }
 
-            // Mark board at board index with number_to_mark
-            marked_boards[board_idx] = mark_board(*number_to_mark, &marked_boards[board_idx]);
+            // Mark board with number_to_mark
+            *board = mark_board(*number_to_mark, board);
 
-            // Check if board is a winner, and return it
-            if is_board_winner(&marked_boards[board_idx]) {
-                losing_result = Result {
-                    board: marked_boards[board_idx].clone(),
-                    number: *number_to_mark,
-                };
+            if is_board_winner(board) {
+                winners.push(board_idx);
             }
         }
+        trace_draw(draw_idx, *number_to_mark, &marked_boards);
+
+        // The last board to win is kept
+        for board_idx in winners {
+            let board = &marked_boards[board_idx];
+            trace_win(board_idx, draw_idx, *number_to_mark, board);
+            losing_result = Some(Winner {
+                board: board.clone(),
+                number: *number_to_mark,
+            });
+        }
     }
 
-    losing_result
+    (marked_boards, losing_result)
 }
 
 fn sum_unmarked_values(board: &Board) -> u32 {
@@ -180,46 +264,173 @@ fn sum_unmarked_values(board: &Board) -> u32 {
     })
 }
 
-fn combine_result(Result { board, number }: &Result) -> u32 {
-    *number as u32 * sum_unmarked_values(&board)
+pub fn combine_result(Winner { board, number }: &Winner) -> u32 {
+    *number as u32 * sum_unmarked_values(board)
 }
 
-pub fn run() {
-    let day4_test: &str = include_str!("../inputs/day4.test");
-    // let day4_test_board: &str = include_str!("../inputs/day4.test.board");
-    let day4_input: &str = include_str!("../inputs/day4");
+// Every number is drawn eventually, so every board wins
+fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut draws: Vec<u16> = (0..100).collect();
+    rng.shuffle(&mut draws);
+    let marks: Vec<String> = draws.iter().map(u16::to_string).collect();
+    let boards: Vec<String> = (0..size.max(1))
+        .map(|_| {
+            rng.shuffle(&mut draws);
+            draws[..25]
+                .chunks(5)
+                .map(|row| {
+                    let row: Vec<String> = row.iter().map(|n| format!("{:>2}", n)).collect();
+                    row.join(" ")
+                })
+                .collect::<Vec<String>>()
+                .join("\n")
+        })
+        .collect();
+    format!("{}\n\n{}\n", marks.join(","), boards.join("\n\n"))
+}
+
+pub struct Day4;
+
+impl Solution for Day4 {
+    type Input = Input;
+
+    fn parse(&self, input: &str) -> Result<Input> {
+        let input = parse_all(input, parse_input)?;
+        validate(&input)?;
+        Ok(input)
+    }
+
+    fn part1(&self, input: &Input) -> Result<Answer> {
+        part1(input)
+            .map(|result| combine_result(&result).into())
+            .ok_or_else(|| Error::NoSolution("No board ever wins".to_string()))
+    }
+
+    fn part2(&self, input: &Input) -> Result<Answer> {
+        part2(input)
+            .map(|result| combine_result(&result).into())
+            .ok_or_else(|| Error::NoSolution("No board ever wins".to_string()))
+    }
+
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        Some(generate(rng, size))
+    }
 
-    // Experiments
-    let mark_str: &str = "7,4,9,5,11,17";
-    assert_eq!(parse_marks(mark_str).unwrap().1, vec![7, 4, 9, 5, 11, 17]);
-    // println!("{:?}", parse_boards(day4_test_board));
+    fn pictures(&self, input: &Input) -> Vec<Picture> {
+        vec![Picture {
+            name: "boards",
+            grid: board_grid(&play(input).0),
+            palette: Palette::Labels,
+        }]
+    }
+}
 
-    // Test inputs
-    let day4_test_input = parse_input(day4_test).unwrap().1;
-    let day4_input = parse_input(day4_input).unwrap().1;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    // Test mark_board and is_board_winner
-    let board = day4_test_input.boards[0].clone();
-    let marks: Vec<Vec<u16>> = vec![
-        vec![22, 13, 17, 11, 0],
-        vec![22, 8, 21, 6, 1],
-        vec![1, 12, 20, 15, 19],
-        vec![0, 24, 7, 5, 19],
-    ];
-    marks.iter().for_each(|x| {
+    #[test]
+    fn parses_marks() {
+        let mark_str: &str = "7,4,9,5,11,17";
         assert_eq!(
-            is_board_winner(
+            parse_marks(mark_str).map(|r| r.1),
+            Ok(vec![7, 4, 9, 5, 11, 17])
+        );
+    }
+
+    #[test]
+    fn full_rows_and_columns_win() {
+        let day4_test: &str = include_str!("../inputs/day4.test");
+        let day4_test_input = parse_all(day4_test, parse_input).unwrap();
+        let board = day4_test_input.boards[0].clone();
+        // Two rows and two columns of the first board
+        let marks: Vec<Vec<u16>> = vec![
+            vec![22, 13, 17, 11, 0],
+            vec![22, 8, 21, 6, 1],
+            vec![1, 12, 20, 15, 19],
+            vec![0, 24, 7, 5, 19],
+        ];
+        marks.iter().for_each(|x| {
+            assert!(is_board_winner(
                 &x.iter()
                     .fold(board.clone(), |acc, y| { mark_board(*y, &acc) })
-            ),
-            true
-        )
-    });
+            ))
+        });
+    }
+
+    #[test]
+    fn partial_rows_do_not_win() {
+        let day4_test: &str = include_str!("../inputs/day4.test");
+        let board = parse_all(day4_test, parse_input).unwrap().boards[0].clone();
+        let marked = [22, 13, 17, 11]
+            .iter()
+            .fold(board, |acc, y| mark_board(*y, &acc));
+        assert!(!is_board_winner(&marked));
+    }
 
-    // Determine results
-    let result_one = combine_result(&part1(&day4_input).unwrap());
-    println!("Part 1: {:?}", result_one);
+    #[test]
+    fn the_first_row_can_be_indented() {
+        let input = parse_all("1,2\n\n 1  2\n 3  4\n", parse_input).unwrap();
+        assert_eq!(input.boards[0][0][1].value, 2);
+    }
 
-    let result_two = combine_result(&part2(&day4_input));
-    println!("Part 2: {:?}", result_two);
+    #[test]
+    fn explains_the_draw_each_board_wins_on() {
+        let day4_test: &str = include_str!("../inputs/day4.test");
+        let input = parse_all(day4_test, parse_input).unwrap();
+        let (_, events) = trace::record(|| part2(&input));
+        let wins: Vec<&Event> = events.iter().filter(|e| e.name == "win").collect();
+        let boards_and_draws: Vec<(Option<i64>, Option<i64>)> = wins
+            .iter()
+            .map(|e| (e.number("board"), e.number("draw")))
+            .collect();
+        assert_eq!(
+            boards_and_draws,
+            vec![
+                (Some(3), Some(12)),
+                (Some(1), Some(14)),
+                (Some(2), Some(15))
+            ]
+        );
+        assert!(wins[2].message.ends_with("for a score of 1924"));
+    }
+
+    #[test]
+    fn every_draw_has_a_picture_of_the_boards() {
+        let day4_test: &str = include_str!("../inputs/day4.test");
+        let input = parse_all(day4_test, parse_input).unwrap();
+        let (_, events) = trace::record(|| part1(&input));
+        let draws: Vec<&Event> = events.iter().filter(|e| e.name == "draw").collect();
+        assert_eq!(draws.len(), 12);
+        // The third board won with its top row, 14 21 17 24 4
+        let Some(trace::Value::Grid(boards)) = draws[11].get("boards") else {
+            panic!("No boards in {:?}", draws[11]);
+        };
+        assert_eq!(boards.len(), 11);
+        assert_eq!(boards[6][..5], [Some(2); 5]);
+        // 22 and 13 of the first row were never drawn
+        assert_eq!(
+            boards[0][..5],
+            [Some(0), Some(0), Some(1), Some(1), Some(1)]
+        );
+    }
+
+    #[test]
+    fn plays_until_every_board_has_won() {
+        let day4_test: &str = include_str!("../inputs/day4.test");
+        let input = parse_all(day4_test, parse_input).unwrap();
+        let (boards, last) = play(&input);
+        assert!(boards.iter().all(is_board_winner));
+        assert_eq!(last.map(|winner| winner.number), Some(13));
+        // Only the marked numbers of boards that won are 2
+        let grid = board_grid(&boards);
+        assert!(grid.iter().flatten().flatten().all(|n| *n == 0 || *n == 2));
+    }
+
+    #[test]
+    fn rejects_boards_that_are_not_square() {
+        let input = "1,2\n\n1 2\n3 4\n5 6\n";
+        let input = parse_all(input, parse_input).unwrap();
+        assert!(matches!(validate(&input), Err(Error::Validation(_))));
+    }
 }
@@ -1,36 +1,45 @@
 // This one is unfinished... sad...
-use super::lib::Res;
+use super::generate::Rng;
+use super::image::{Palette, Picture};
+use super::lib::{expected, list, parse_all, Res, Result};
+use super::solution::{Answer, Solution};
+use super::trace::Grid;
 use geo::{
     algorithm::line_intersection::{line_intersection, LineIntersection},
     Coordinate, Line,
 };
 use nom::{
     bytes::complete::tag,
-    character::complete::{char, i64, newline},
-    multi::separated_list1,
+    character::complete::{char, newline, u32},
+    error::context,
+    sequence::separated_pair,
 };
 use std::collections::HashSet;
 
-type Point = Coordinate<f64>;
-type LineSegment = Line<f64>;
+pub type Point = Coordinate<f64>;
+pub type LineSegment = Line<f64>;
 
 pub fn parse_point(input: &str) -> Res<&str, Point> {
-    // Technically this would also parse 1,2,3
-    let (input, result) = separated_list1(char(','), i64)(input)?;
+    let (input, (x, y)) = context("point", separated_pair(u32, char(','), u32))(input)?;
     Ok((
         input,
         Point {
-            x: result[0] as f64,
-            y: result[1] as f64,
+            x: x as f64,
+            y: y as f64,
         },
     ))
 }
 
+fn parse_line(input: &str) -> Res<&str, (Point, Point)> {
+    context(
+        "line",
+        separated_pair(parse_point, expected("\" -> \"", tag(" -> ")), parse_point),
+    )(input)
+}
+
 fn parse_segments(input: &str) -> Res<&str, Option<LineSegment>> {
-    let (input, result) = separated_list1(tag(" -> "), parse_point)(input)?;
+    let (input, (coord0, coord1)) = parse_line(input)?;
     Ok((input, {
-        let coord0 = result[0];
-        let coord1 = result[1];
         let line_segment = LineSegment::new(coord0, coord1);
         match (coord0.x == coord1.x, coord0.y == coord1.y) {
             (true, _) => Some(line_segment),
@@ -40,28 +49,19 @@ fn parse_segments(input: &str) -> Res<&str, Option<LineSegment>> {
     }))
 }
 
-fn parse_part_one(input: &str) -> Res<&str, Vec<LineSegment>> {
-    let (input, result) = separated_list1(newline, parse_segments)(input)?;
-    Ok((
-        input,
-        result
-            .iter()
-            .filter(|x| x.is_some())
-            .map(|x| x.clone().unwrap())
-            .collect(),
-    ))
+pub fn parse_part_one(input: &str) -> Res<&str, Vec<LineSegment>> {
+    let (input, result) = list(newline, parse_segments)(input)?;
+    Ok((input, result.into_iter().flatten().collect()))
 }
 
 fn parse_diagonals(input: &str) -> Res<&str, Option<LineSegment>> {
-    let (input, result) = separated_list1(tag(" -> "), parse_point)(input)?;
+    let (input, (coord0, coord1)) = parse_line(input)?;
     Ok((input, {
-        let coord0 = result[0];
-        let coord1 = result[1];
         let is_horizontal = coord0.x == coord1.x;
         let is_vertical = coord0.y == coord1.y;
         let line_segment = LineSegment::new(coord0, coord1);
         let slope_is_one = line_segment.slope().abs() == 1.0;
-        if vec![is_horizontal, is_vertical, slope_is_one]
+        if [is_horizontal, is_vertical, slope_is_one]
             .iter()
             .any(|x| *x)
         {
@@ -72,16 +72,9 @@ fn parse_diagonals(input: &str) -> Res<&str, Option<LineSegment>> {
     }))
 }
 
-fn parse_part_two(input: &str) -> Res<&str, Vec<LineSegment>> {
-    let (input, result) = separated_list1(newline, parse_diagonals)(input)?;
-    Ok((
-        input,
-        result
-            .iter()
-            .filter(|x| x.is_some())
-            .map(|x| x.clone().unwrap())
-            .collect(),
-    ))
+pub fn parse_part_two(input: &str) -> Res<&str, Vec<LineSegment>> {
+    let (input, result) = list(newline, parse_diagonals)(input)?;
+    Ok((input, result.into_iter().flatten().collect()))
 }
 
 fn swap_line_segment(input: &LineSegment) -> LineSegment {
@@ -96,17 +89,15 @@ fn order_line_segment(input: &LineSegment) -> LineSegment {
     let is_horizonal_ = input.start.y > input.end.y;
     let is_vertical = input.start.y == input.end.y;
     let is_vertical_ = input.start.x > input.end.x;
-    if is_horizontal && is_horizonal_ {
-        swap_line_segment(&input)
-    } else if is_vertical && is_vertical_ {
-        swap_line_segment(&input)
+    if (is_horizontal && is_horizonal_) || (is_vertical && is_vertical_) {
+        swap_line_segment(input)
     } else {
-        input.clone()
+        *input
     }
 }
 
 fn generate_points_part_one(input: &LineSegment) -> Vec<(usize, usize)> {
-    let ordered = order_line_segment(&input);
+    let ordered = order_line_segment(input);
     let mut ret: Vec<(usize, usize)> = Vec::new();
     if ordered.start.x == ordered.end.x {
         for y in ordered.start.y as usize..ordered.end.y as usize + 1 {
@@ -117,21 +108,21 @@ fn generate_points_part_one(input: &LineSegment) -> Vec<(usize, usize)> {
             ret.push((x, ordered.start.y as usize));
         }
     } else {
-        panic!("generate_points_part_one: {:?}", ordered.slope())
+        // parse_part_one only keeps horizontal and vertical lines
+        unreachable!("generate_points_part_one: {:?}", ordered.slope())
     }
     ret
 }
 
 fn build_range(x: f64, y: f64, reverse: bool) -> Vec<usize> {
-    let mut vec: Vec<usize> = Vec::new();
     // (x as usize..y as usize + 1).collect();
     if reverse {
-        vec = (y as usize..x as usize + 1).collect();
+        let mut vec: Vec<usize> = (y as usize..x as usize + 1).collect();
         vec.reverse();
+        vec
     } else {
-        vec = (x as usize..y as usize + 1).collect();
+        (x as usize..y as usize + 1).collect()
     }
-    vec
 }
 
 fn build_vec_hv(input: &LineSegment) -> Vec<(usize, usize)> {
@@ -146,7 +137,8 @@ fn build_vec_hv(input: &LineSegment) -> Vec<(usize, usize)> {
     } else if y_is_flat {
         x.iter().map(|x| (*x, input.start.y as usize)).collect()
     } else {
-        panic!("build_vec_hv: Oh no...")
+        // Diagonals are handled by build_vec_diagonal
+        unreachable!("build_vec_hv: {:?}", input.slope())
     }
 }
 
@@ -165,14 +157,14 @@ fn build_vec_diagonal(input: &LineSegment) -> Vec<(usize, usize)> {
 fn generate_points_part_two(input: &LineSegment) -> Vec<(usize, usize)> {
     let mut ret: Vec<(usize, usize)> = Vec::new();
     if input.slope() == 1.0 || input.slope() == -1.0 {
-        ret.append(&mut build_vec_diagonal(&input));
+        ret.append(&mut build_vec_diagonal(input));
     } else {
-        ret.append(&mut build_vec_hv(&input));
+        ret.append(&mut build_vec_hv(input));
     }
     ret
 }
 
-fn part1(input: &Vec<LineSegment>) -> usize {
+pub fn part1(input: &[LineSegment]) -> usize {
     // Maintain a set of points
     let mut set: HashSet<(usize, usize)> = HashSet::new();
     for x in 0..input.len() {
@@ -200,7 +192,7 @@ fn part1(input: &Vec<LineSegment>) -> usize {
     set.len()
 }
 
-fn part2(input: &Vec<LineSegment>) -> usize {
+pub fn part2(input: &[LineSegment]) -> usize {
     // Maintain a set of points
     let mut set: HashSet<(usize, usize)> = HashSet::new();
     for x in 0..input.len() {
@@ -230,71 +222,189 @@ fn part2(input: &Vec<LineSegment>) -> usize {
     set.len()
 }
 
-fn make_point(x: usize, y: usize) -> Point {
-    Point {
-        x: x as f64,
-        y: y as f64,
+// How many segments cover each point, row by row
+pub fn overlaps(segments: &[LineSegment]) -> Grid {
+    let points: Vec<(usize, usize)> = segments.iter().flat_map(generate_points_part_two).collect();
+    let width = points.iter().map(|(x, _)| x + 1).max().unwrap_or(0);
+    let height = points.iter().map(|(_, y)| y + 1).max().unwrap_or(0);
+    let mut grid: Grid = vec![vec![None; width]; height];
+    for (x, y) in points {
+        *grid[y][x].get_or_insert(0) += 1;
+    }
+    grid
+}
+
+pub struct Vents {
+    pub part_one: Vec<LineSegment>,
+    pub part_two: Vec<LineSegment>,
+}
+
+// Horizontal, vertical and 45 degree segments inside a 1000 x 1000 grid
+fn generate(rng: &mut Rng, size: usize) -> String {
+    let directions = [
+        (1, 0),
+        (-1, 0),
+        (0, 1),
+        (0, -1),
+        (1, 1),
+        (1, -1),
+        (-1, 1),
+        (-1, -1),
+    ];
+    (0..size.max(1))
+        .map(|_| {
+            let (dx, dy): (isize, isize) = *rng.choose(&directions);
+            let length = rng.between(1, 100) as isize;
+            // Start far enough from the edge for the segment to fit
+            let mut start = |step: isize| {
+                let start = rng.below(1000 - (step.abs() * length) as usize) as isize;
+                if step < 0 {
+                    start + length
+                } else {
+                    start
+                }
+            };
+            let (x1, y1) = (start(dx), start(dy));
+            let (x2, y2) = (x1 + dx * length, y1 + dy * length);
+            format!("{},{} -> {},{}\n", x1, y1, x2, y2)
+        })
+        .collect()
+}
+
+pub struct Day5;
+
+impl Solution for Day5 {
+    type Input = Vents;
+
+    fn parse(&self, input: &str) -> Result<Vents> {
+        Ok(Vents {
+            part_one: parse_all(input, parse_part_one)?,
+            part_two: parse_all(input, parse_part_two)?,
+        })
+    }
+
+    fn part1(&self, input: &Vents) -> Result<Answer> {
+        Ok(part1(&input.part_one).into())
+    }
+
+    fn part2(&self, input: &Vents) -> Result<Answer> {
+        Ok(part2(&input.part_two).into())
+    }
+
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        Some(generate(rng, size))
+    }
+
+    fn pictures(&self, input: &Vents) -> Vec<Picture> {
+        vec![Picture {
+            name: "overlaps",
+            grid: overlaps(&input.part_two),
+            palette: Palette::Heat,
+        }]
     }
 }
 
-pub fn run() {
-    let test_str: &str = include_str!("../inputs/day5.test");
-    let input_str: &str = include_str!("../inputs/day5");
-
-    // Test parsers
-    assert_eq!(parse_point("0,1").unwrap().1, make_point(0, 1));
-
-    let test_part_one = parse_part_one(test_str).unwrap().1;
-    let input_part_one = parse_part_one(input_str).unwrap().1;
-
-    // Part 1
-    println!("Part 1: {:?}", part1(&input_part_one));
-
-    let test_part_two = parse_part_two(test_str).unwrap().1;
-    let input_part_two = parse_part_two(input_str).unwrap().1;
-
-    // Testing various functions
-    let segment = LineSegment {
-        start: make_point(4, 1),
-        end: make_point(4, 3),
-    };
-    assert_eq!(build_vec_hv(&segment), vec![(4, 1), (4, 2), (4, 3)]);
-    let segment = LineSegment {
-        start: make_point(1, 4),
-        end: make_point(3, 4),
-    };
-    assert_eq!(build_vec_hv(&segment), vec![(1, 4), (2, 4), (3, 4)]);
-    let segment = LineSegment {
-        start: make_point(4, 3),
-        end: make_point(4, 1),
-    };
-    assert_eq!(build_vec_hv(&segment), vec![(4, 3), (4, 2), (4, 1)]);
-    let segment = LineSegment {
-        start: make_point(3, 4),
-        end: make_point(1, 4),
-    };
-    assert_eq!(build_vec_hv(&segment), vec![(3, 4), (2, 4), (1, 4)]);
-    let segment = LineSegment {
-        start: make_point(3, 3),
-        end: make_point(1, 1),
-    };
-    assert_eq!(build_vec_diagonal(&segment), vec![(3, 3), (2, 2), (1, 1)]);
-    let segment = LineSegment {
-        start: make_point(1, 1),
-        end: make_point(3, 3),
-    };
-    assert_eq!(build_vec_diagonal(&segment), vec![(1, 1), (2, 2), (3, 3)]);
-    let segment = LineSegment {
-        start: make_point(1, 3),
-        end: make_point(3, 1),
-    };
-    assert_eq!(build_vec_diagonal(&segment), vec![(1, 3), (2, 2), (3, 1)]);
-    let segment = LineSegment {
-        start: make_point(3, 1),
-        end: make_point(1, 3),
-    };
-    assert_eq!(build_vec_diagonal(&segment), vec![(3, 1), (2, 2), (1, 3)]);
-
-    // Part2
-    println!("Part 2: {:?}", part2(&input_part_two));
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_point(x: usize, y: usize) -> Point {
+        Point {
+            x: x as f64,
+            y: y as f64,
+        }
+    }
+
+    #[test]
+    fn parses_points() {
+        assert_eq!(parse_point("0,1").map(|r| r.1), Ok(make_point(0, 1)));
+    }
+
+    #[test]
+    fn rejects_incomplete_lines() {
+        assert!(parse_all("0,9 -> 5", parse_part_one).is_err());
+        assert!(parse_all("0,9 => 5,9", parse_part_one).is_err());
+    }
+
+    #[test]
+    fn keeps_horizontal_and_vertical_lines_in_part_one() {
+        let lines = parse_all("0,9 -> 5,9\n8,0 -> 0,8\n7,0 -> 7,4\n", parse_part_one).unwrap();
+        assert_eq!(lines.len(), 2);
+    }
+
+    #[test]
+    fn keeps_diagonals_in_part_two() {
+        let lines = parse_all("0,9 -> 5,9\n8,0 -> 0,8\n7,0 -> 6,4\n", parse_part_two).unwrap();
+        assert_eq!(lines.len(), 2);
+    }
+
+    #[test]
+    fn builds_horizontal_and_vertical_points() {
+        let segment = LineSegment {
+            start: make_point(4, 1),
+            end: make_point(4, 3),
+        };
+        assert_eq!(build_vec_hv(&segment), vec![(4, 1), (4, 2), (4, 3)]);
+        let segment = LineSegment {
+            start: make_point(1, 4),
+            end: make_point(3, 4),
+        };
+        assert_eq!(build_vec_hv(&segment), vec![(1, 4), (2, 4), (3, 4)]);
+        let segment = LineSegment {
+            start: make_point(4, 3),
+            end: make_point(4, 1),
+        };
+        assert_eq!(build_vec_hv(&segment), vec![(4, 3), (4, 2), (4, 1)]);
+        let segment = LineSegment {
+            start: make_point(3, 4),
+            end: make_point(1, 4),
+        };
+        assert_eq!(build_vec_hv(&segment), vec![(3, 4), (2, 4), (1, 4)]);
+    }
+
+    #[test]
+    fn builds_diagonal_points() {
+        let segment = LineSegment {
+            start: make_point(3, 3),
+            end: make_point(1, 1),
+        };
+        assert_eq!(build_vec_diagonal(&segment), vec![(3, 3), (2, 2), (1, 1)]);
+        let segment = LineSegment {
+            start: make_point(1, 1),
+            end: make_point(3, 3),
+        };
+        assert_eq!(build_vec_diagonal(&segment), vec![(1, 1), (2, 2), (3, 3)]);
+        let segment = LineSegment {
+            start: make_point(1, 3),
+            end: make_point(3, 1),
+        };
+        assert_eq!(build_vec_diagonal(&segment), vec![(1, 3), (2, 2), (3, 1)]);
+        let segment = LineSegment {
+            start: make_point(3, 1),
+            end: make_point(1, 3),
+        };
+        assert_eq!(build_vec_diagonal(&segment), vec![(3, 1), (2, 2), (1, 3)]);
+    }
+
+    #[test]
+    fn counts_overlaps_for_the_heatmap() {
+        let day5_test: &str = include_str!("../inputs/day5.test");
+        let count_dangerous = |segments: &[LineSegment]| {
+            let grid = overlaps(segments);
+            grid.iter().flatten().filter(|n| **n >= Some(2)).count()
+        };
+        // The published answers for the sample
+        assert_eq!(
+            count_dangerous(&parse_all(day5_test, parse_part_one).unwrap()),
+            5
+        );
+        assert_eq!(
+            count_dangerous(&parse_all(day5_test, parse_part_two).unwrap()),
+            12
+        );
+        let grid = overlaps(&parse_all(day5_test, parse_part_two).unwrap());
+        assert_eq!((grid.len(), grid[0].len()), (10, 10));
+        assert_eq!(grid[4][4..7], [Some(3), Some(1), Some(3)]);
+        assert_eq!(grid[0][1], None);
+    }
 }
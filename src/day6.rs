@@ -1,20 +1,18 @@
-use super::lib::Res;
+use super::generate::Rng;
+use super::lib::{list, parse_all, Error, Res, Result};
+use super::solution::{Answer, Solution};
+use super::trace::{self, Event};
 use nom::{
-    character::complete::{char, i64},
-    multi::separated_list1,
+    character::complete::{char, u32},
+    error::context,
 };
 use std::collections::HashMap;
 
-#[derive(Debug, Clone)]
-struct Fish {
-    countdown: usize,
-}
-
 // First usize is day, second usize is count
-type FishCounts = HashMap<usize, usize>;
+pub type FishCounts = HashMap<usize, usize>;
 
-fn parse_fishes(input: &str) -> Res<&str, FishCounts> {
-    let (input, result) = separated_list1(char(','), i64)(input)?;
+pub fn parse_fishes(input: &str) -> Res<&str, FishCounts> {
+    let (input, result) = context("timers", list(char(','), u32))(input)?;
     let mut hmap: FishCounts = HashMap::new();
     for x in result {
         *hmap.entry(x as usize).or_insert(0) += 1;
@@ -22,38 +20,82 @@ fn parse_fishes(input: &str) -> Res<&str, FishCounts> {
     Ok((input, hmap))
 }
 
-fn simulate(input: &FishCounts) -> FishCounts {
+pub fn simulate(input: &FishCounts) -> FishCounts {
     let mut result: FishCounts = HashMap::new();
     for day in 0..8 + 1 {
         let count = input.get(&day);
-        match count {
-            Some(c) => {
-                if day == 0 {
-                    *result.entry(6).or_insert(0) += *c;
-                    *result.entry(8).or_insert(0) += *c;
-                } else {
-                    *result.entry(day - 1).or_insert(0) += *c;
-                }
+        if let Some(c) = count {
+            if day == 0 {
+                *result.entry(6).or_insert(0) += *c;
+                *result.entry(8).or_insert(0) += *c;
+            } else {
+                *result.entry(day - 1).or_insert(0) += *c;
             }
-            None => (),
         }
     }
     result
 }
 
-fn part1(number_of_days: usize, input: &FishCounts) -> usize {
+// How many fish there are of every age, from 0 to 8
+fn ages(fishes: &FishCounts) -> Vec<i64> {
+    (0..=8)
+        .map(|age| fishes.get(&age).copied().unwrap_or(0) as i64)
+        .collect()
+}
+
+pub fn part1(number_of_days: usize, input: &FishCounts) -> usize {
     let mut result: FishCounts = input.clone();
-    for _ in 0..number_of_days {
+    for day in 1..=number_of_days {
         result = simulate(&result);
-        // println!("Finished day: {:?}", day);
+        trace::emit(|| {
+            let population: usize = result.values().sum();
+            let spawning = result.get(&8).copied().unwrap_or(0);
+            Event::new(
+                "day",
+                format!(
+                    "After day {}: {} fish, {} of them newborn",
+                    day, population, spawning
+                ),
+            )
+            .with("day", day)
+            .with("population", population)
+            .with("newborn", spawning)
+            .with("ages", ages(&result))
+        });
     }
     result.into_values().sum()
 }
 
-pub fn run() {
-    let test_str: &str = "3,4,3,1,2";
-    let input_str = include_str!("../inputs/day6");
-    // let test = parse_fishes(test_str).unwrap().1;
-    let input = parse_fishes(input_str).unwrap().1;
-    println!("{:?}", part1(256, &input));
+fn generate(rng: &mut Rng, size: usize) -> String {
+    let timers: Vec<String> = (0..size.max(1))
+        .map(|_| rng.between(1, 5).to_string())
+        .collect();
+    format!("{}\n", timers.join(","))
+}
+
+pub struct Day6;
+
+impl Solution for Day6 {
+    type Input = FishCounts;
+
+    fn parse(&self, input: &str) -> Result<FishCounts> {
+        let fishes = parse_all(input, parse_fishes)?;
+        // simulate only tracks timers 0 to 8, anything else would silently vanish
+        if let Some(timer) = fishes.keys().find(|timer| **timer > 8) {
+            return Err(Error::Validation(format!(
+                "A fish timer must be between 0 and 8, got {}",
+                timer
+            )));
+        }
+        Ok(fishes)
+    }
+
+    // Only the 256 day simulation was ever submitted, so part 1 is left unimplemented
+    fn part2(&self, input: &FishCounts) -> Result<Answer> {
+        Ok(part1(256, input).into())
+    }
+
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        Some(generate(rng, size))
+    }
 }
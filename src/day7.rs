@@ -1,74 +1,100 @@
-use super::lib::Res;
+use super::generate::Rng;
+use super::lib::{fold_map, list, parse_all, Error, Part, Res, Result, Sum};
+use super::solution::{Answer, Solution};
+use super::trace::{self, Event};
 use nom::{
-    character::complete::{char, i64},
-    multi::separated_list1,
+    character::complete::{char, u32},
+    error::context,
 };
 use std::collections::HashMap;
 
 // First one is location, second is count
-type Locations = HashMap<usize, usize>;
+pub type Locations = HashMap<usize, Sum<usize>>;
 
-fn parse_crabs(input: &str) -> Res<&str, Locations> {
-    let (input, result) = separated_list1(char(','), i64)(input)?;
-    let mut hmap: Locations = HashMap::new();
-    for x in result {
-        *hmap.entry(x as usize).or_insert(0) += 1;
-    }
+pub fn parse_crabs(input: &str) -> Res<&str, Locations> {
+    let (input, result) = context("positions", list(char(','), u32))(input)?;
+    let hmap = fold_map(result, |x| HashMap::from([(x as usize, Sum(1))]));
     Ok((input, hmap))
 }
 
-type FuelCount = HashMap<usize, usize>;
-
-#[derive(Debug)]
-enum Part {
-    One,
-    Two,
-}
+type FuelCount = HashMap<usize, Sum<usize>>;
 
 fn get_triangular_number(input: usize) -> usize {
     input * (input + 1) / 2
 }
 
-fn simulate(input: &Locations, part: &Part, debug: bool) -> Option<usize> {
+pub fn simulate(input: &Locations, part: &Part) -> Option<usize> {
     // All possible horizontal positions
-    let min = input.keys().min().unwrap();
-    let max = input.keys().max().unwrap();
+    let min = input.keys().min()?;
+    let max = input.keys().max()?;
 
     // Brute force compute the fuel costs for moving to each horizontal position
-    let mut fuel_counts: FuelCount = HashMap::new();
-    for move_to in *min..*max {
-        let mut fuel: isize = 0;
-        for (location, count) in input {
-            let movement = (*location as isize - move_to as isize).abs();
+    let fuel_counts: FuelCount = fold_map(*min..*max, |move_to| {
+        let fuel = fold_map(input, |(location, Sum(count))| {
+            let movement = location.abs_diff(move_to);
             let cost = match part {
                 Part::One => movement,
-                Part::Two => get_triangular_number(movement as usize) as isize,
+                Part::Two => get_triangular_number(movement),
             };
-            fuel += cost * *count as isize;
-        }
-        *fuel_counts.entry(move_to).or_insert(0) += fuel as usize;
+            Sum(cost * count)
+        });
+        HashMap::from([(move_to, fuel)])
+    });
+
+    // Determine the minimum fuel count
+    let (position, Sum(fuel)) = fuel_counts.iter().min_by(|a, b| a.1.cmp(b.1))?;
+    trace::emit(|| {
+        Event::new(
+            "cheapest",
+            format!(
+                "Of the positions {} to {}, moving every crab to {} takes the least fuel: {}",
+                min, max, position, fuel
+            ),
+        )
+        .with("position", *position)
+        .with("fuel", *fuel)
+    });
+    Some(*fuel)
+}
+
+// simulate only tries positions between the outermost crabs
+fn no_position() -> Error {
+    Error::NoSolution("The crabs need at least two different positions".to_string())
+}
+
+// simulate needs at least two different positions
+fn generate(rng: &mut Rng, size: usize) -> String {
+    let count = size.max(2);
+    let mut positions: Vec<usize> = (0..count).map(|_| rng.below(2 * count)).collect();
+    if positions.iter().all(|p| *p == positions[0]) {
+        positions[0] += 1;
     }
+    let positions: Vec<String> = positions.iter().map(usize::to_string).collect();
+    format!("{}\n", positions.join(","))
+}
+
+pub struct Day7;
+
+impl Solution for Day7 {
+    type Input = Locations;
 
-    if debug {
-        let mut keys: Vec<_> = fuel_counts.clone().into_iter().collect();
-        keys.sort_by(|x, y| x.0.cmp(&y.0));
-        for (k, v) in keys {
-            println!("key: {:?} value: {:?}", k, v);
-        }
+    fn parse(&self, input: &str) -> Result<Locations> {
+        parse_all(input, parse_crabs)
     }
 
-    // Determine the minimum fuel count
-    fuel_counts
-        .iter()
-        .min_by(|a, b| a.1.cmp(&b.1))
-        .map(|(_k, v)| *v)
-}
+    fn part1(&self, input: &Locations) -> Result<Answer> {
+        simulate(input, &Part::One)
+            .map(Answer::from)
+            .ok_or_else(no_position)
+    }
+
+    fn part2(&self, input: &Locations) -> Result<Answer> {
+        simulate(input, &Part::Two)
+            .map(Answer::from)
+            .ok_or_else(no_position)
+    }
 
-pub fn run() {
-    let test_str: &str = "16,1,2,0,4,2,7,1,2,14";
-    let input_str = include_str!("../inputs/day7");
-    let test = parse_crabs(test_str).unwrap().1;
-    let input = parse_crabs(input_str).unwrap().1;
-    println!("Part 1: {:?}", simulate(&input, &Part::One, false));
-    println!("Part 2: {:?}", simulate(&input, &Part::Two, false));
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        Some(generate(rng, size))
+    }
 }
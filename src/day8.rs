@@ -1,30 +1,41 @@
-use super::lib::Res;
+use super::generate::Rng;
+use super::lib::{expected, list, parse_all, Res, Result};
+use super::solution::{Answer, Solution};
 use nom::{
     bytes::complete::tag,
     character::complete::{alpha1, newline, space1},
+    error::context,
     multi::separated_list1,
     sequence::tuple,
 };
 
 #[derive(Debug)]
-struct InsAndOuts<'a> {
-    ins: Vec<&'a str>,
-    outs: Vec<&'a str>,
+pub struct InsAndOuts {
+    // Only needed to decode the outputs in part 2
+    pub ins: Vec<String>,
+    pub outs: Vec<String>,
 }
 
-fn parse_line(input: &str) -> Res<&str, InsAndOuts> {
-    let (input, ins) = separated_list1(space1, alpha1)(input)?;
-    let (input, _) = tuple((space1, tag("|"), space1))(input)?;
-    let (input, outs) = separated_list1(space1, alpha1)(input)?;
-    Ok((input, InsAndOuts { ins, outs }))
+pub fn parse_line(input: &str) -> Res<&str, InsAndOuts> {
+    // The patterns end at the |, so they can't use list
+    let (input, ins) = context("patterns", separated_list1(space1, alpha1))(input)?;
+    let (input, _) = expected("\" | \"", tuple((space1, tag("|"), space1)))(input)?;
+    let (input, outs) = context("outputs", list(space1, alpha1))(input)?;
+    Ok((
+        input,
+        InsAndOuts {
+            ins: ins.into_iter().map(String::from).collect(),
+            outs: outs.into_iter().map(String::from).collect(),
+        },
+    ))
 }
 
-fn parse_lines(input: &str) -> Res<&str, Vec<InsAndOuts>> {
-    let (input, result) = separated_list1(newline, parse_line)(input)?;
+pub fn parse_lines(input: &str) -> Res<&str, Vec<InsAndOuts>> {
+    let (input, result) = list(newline, parse_line)(input)?;
     Ok((input, result))
 }
 
-fn find_obvious_numbers(input: &str) -> Option<usize> {
+pub fn find_obvious_numbers(input: &str) -> Option<usize> {
     match input.len() {
         2 => Some(1),
         4 => Some(4),
@@ -34,27 +45,64 @@ fn find_obvious_numbers(input: &str) -> Option<usize> {
     }
 }
 
-pub fn run() {
-    let test_: &str =
-        "be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edb | fdgacbe cefdb cefbgd gcbe";
-    // println!("{:?}", parse_line(test).unwrap().1);
-
-    let test_str = include_str!("../inputs/day8.test");
-    let input_str = include_str!("../inputs/day8");
-    let test = parse_lines(test_str).unwrap().1;
-    let input = parse_lines(input_str).unwrap().1;
-
-    let result: usize = input
-        .iter()
-        .map(|InsAndOuts { ins: _, outs }| {
-            let mut obvious_numbers = 0;
-            for out in outs {
-                if find_obvious_numbers(&out).is_some() {
-                    obvious_numbers += 1
-                }
-            }
-            obvious_numbers
+// The segments lit for 0 to 9 on a correctly wired display
+const DIGITS: [&str; 10] = [
+    "abcefg", "cf", "acdeg", "acdfg", "bcdf", "abdfg", "abdefg", "acf", "abcdefg", "abcdfg",
+];
+
+// Each line rewires the segments, then lists the ten digits and four outputs
+// with their letters in any order
+fn generate(rng: &mut Rng, size: usize) -> String {
+    (0..size.max(1))
+        .map(|_| {
+            let mut wiring: Vec<char> = "abcdefg".chars().collect();
+            rng.shuffle(&mut wiring);
+            let mut digits: Vec<usize> = (0..10).collect();
+            rng.shuffle(&mut digits);
+            let outputs: Vec<usize> = (0..4).map(|_| rng.below(10)).collect();
+            let mut scramble = |digit: &usize| {
+                let mut wires: Vec<char> = DIGITS[*digit]
+                    .chars()
+                    .map(|c| wiring[c as usize - 'a' as usize])
+                    .collect();
+                rng.shuffle(&mut wires);
+                wires.into_iter().collect::<String>()
+            };
+            let ins: Vec<String> = digits.iter().map(&mut scramble).collect();
+            let outs: Vec<String> = outputs.iter().map(&mut scramble).collect();
+            format!("{} | {}\n", ins.join(" "), outs.join(" "))
         })
-        .sum();
-    println!("{:?}", result);
+        .collect()
+}
+
+pub struct Day8;
+
+impl Solution for Day8 {
+    type Input = Vec<InsAndOuts>;
+
+    fn parse(&self, input: &str) -> Result<Vec<InsAndOuts>> {
+        parse_all(input, parse_lines)
+    }
+
+    fn part1(&self, input: &Vec<InsAndOuts>) -> Result<Answer> {
+        let result: usize = input
+            .iter()
+            .map(|InsAndOuts { ins: _, outs }| {
+                let mut obvious_numbers = 0;
+                for out in outs {
+                    if find_obvious_numbers(out).is_some() {
+                        obvious_numbers += 1
+                    }
+                }
+                obvious_numbers
+            })
+            .sum();
+        Ok(result.into())
+    }
+
+    // Decoding the scrambled segments for part 2 was never finished
+
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        Some(generate(rng, size))
+    }
 }
@@ -1,30 +1,43 @@
-use super::lib::Res;
+use super::generate::Rng;
+use super::image::{Palette, Picture};
+use super::lib::{expected, list, parse_all, Error, Res, Result};
+use super::solution::{Answer, Solution};
+use super::trace::{self, Event, Grid};
 use nom::character::complete::newline;
-use nom::multi::separated_list1;
+use nom::error::context;
 use nom::{character::complete::one_of, multi::many1};
 use std::collections::HashMap;
 extern crate nalgebra as na;
 
 fn parse_single_number(input: &str) -> Res<&str, usize> {
-    let (input, res) = one_of("0123456789")(input)?;
-    Ok((input, res.to_string().parse::<usize>().unwrap()))
+    let (input, res) = expected("a height from 0 to 9", one_of("0123456789"))(input)?;
+    Ok((input, res as usize - '0' as usize))
 }
 
 fn parse_line(input: &str) -> Res<&str, Vec<usize>> {
-    let (input, res) = many1(parse_single_number)(input)?;
-    Ok((input, res.iter().map(|x| *x as usize).collect()))
+    let (input, res) = context("row", many1(parse_single_number))(input)?;
+    Ok((input, res))
 }
 
-type Matrix = na::DMatrix<usize>;
+pub type Matrix = na::DMatrix<usize>;
 
-fn parse_lines(input: &str) -> Res<&str, Matrix> {
-    let (input, res) = separated_list1(newline, parse_line)(input)?;
-    Ok((input, {
-        let num_rows = res.len();
-        let num_cols = res[0].len();
-        // from_vec reads in column major order, but index reads (row, col)
-        Matrix::from_vec(num_cols, num_rows, res.into_iter().flatten().collect()).transpose()
-    }))
+pub fn parse_lines(input: &str) -> Res<&str, Vec<Vec<usize>>> {
+    list(newline, parse_line)(input)
+}
+
+pub fn build_matrix(rows: Vec<Vec<usize>>) -> Result<Matrix> {
+    let num_rows = rows.len();
+    let num_cols = rows[0].len();
+    if let Some(idx) = rows.iter().position(|row| row.len() != num_cols) {
+        return Err(Error::Validation(format!(
+            "Row {} has {} heights, expected {}",
+            idx + 1,
+            rows[idx].len(),
+            num_cols
+        )));
+    }
+    // from_vec reads in column major order, but index reads (row, col)
+    Ok(Matrix::from_vec(num_cols, num_rows, rows.into_iter().flatten().collect()).transpose())
 }
 
 type RowAndCol = (usize, usize);
@@ -64,7 +77,7 @@ fn is_lower_than_neighbors(matrix: &Matrix, row_and_col: &RowAndCol) -> bool {
         && look_right(matrix, row_and_col)
 }
 
-fn part1(matrix: &Matrix) -> usize {
+pub fn part1(matrix: &Matrix) -> usize {
     let mut risk_level: usize = 0;
     for row in 0..matrix.nrows() {
         for col in 0..matrix.ncols() {
@@ -78,6 +91,14 @@ fn part1(matrix: &Matrix) -> usize {
 
 type BasinMatrix = na::DMatrix<Option<usize>>;
 
+// The labels so far, row by row, with 0 for the nines between basins
+fn snapshot(basin_matrix: &BasinMatrix) -> Grid {
+    basin_matrix
+        .row_iter()
+        .map(|row| row.iter().map(|v| v.map(|v| v as i64)).collect())
+        .collect()
+}
+
 // [   | b |   ]
 // [ d | x | e ]
 // [   | g |   ]
@@ -90,32 +111,37 @@ fn propogate(basin_matrix: &mut BasinMatrix, basin_counter: usize) {
                 let is_safe_add_row = row != basin_matrix.nrows() - 1;
                 let is_safe_add_col = col != basin_matrix.ncols() - 1;
                 // b
-                if is_safe_sub_row {
-                    if basin_matrix[(row - 1, col)] != Some(0) {
-                        basin_matrix[(row - 1, col)] = Some(basin_counter);
-                    }
+                if is_safe_sub_row && basin_matrix[(row - 1, col)] != Some(0) {
+                    basin_matrix[(row - 1, col)] = Some(basin_counter);
                 }
                 // d
-                if is_safe_sub_col {
-                    if basin_matrix[(row, col - 1)] != Some(0) {
-                        basin_matrix[(row, col - 1)] = Some(basin_counter);
-                    }
+                if is_safe_sub_col && basin_matrix[(row, col - 1)] != Some(0) {
+                    basin_matrix[(row, col - 1)] = Some(basin_counter);
                 }
                 // e
-                if is_safe_add_col {
-                    if basin_matrix[(row, col + 1)] != Some(0) {
-                        basin_matrix[(row, col + 1)] = Some(basin_counter);
-                    }
+                if is_safe_add_col && basin_matrix[(row, col + 1)] != Some(0) {
+                    basin_matrix[(row, col + 1)] = Some(basin_counter);
                 }
                 // g
-                if is_safe_add_row {
-                    if basin_matrix[(row + 1, col)] != Some(0) {
-                        basin_matrix[(row + 1, col)] = Some(basin_counter);
-                    }
+                if is_safe_add_row && basin_matrix[(row + 1, col)] != Some(0) {
+                    basin_matrix[(row + 1, col)] = Some(basin_counter);
                 }
             }
         }
     }
+    trace::emit(|| {
+        let cells = basin_matrix
+            .iter()
+            .filter(|v| **v == Some(basin_counter))
+            .count();
+        Event::new(
+            "label",
+            format!("Basin {} spreads to {} cells", basin_counter, cells),
+        )
+        .with("basin", basin_counter)
+        .with("cells", cells)
+        .with("labels", snapshot(basin_matrix))
+    });
 }
 
 // [   | b |   ]
@@ -135,43 +161,35 @@ fn fill_larger_number(basin_matrix: &mut BasinMatrix) {
                 let is_safe_add_row = row != basin_matrix.nrows() - 1;
                 let is_safe_add_col = col != basin_matrix.ncols() - 1;
                 // b
-                if is_safe_sub_row {
-                    if basin_matrix[(row - 1, col)] != Some(0) {
-                        if basin_matrix[(row - 1, col)] > basin_matrix[(row, col)] {
-                            basin_matrix[(row, col)] = basin_matrix[(row - 1, col)]
-                        } else {
-                            basin_matrix[(row - 1, col)] = basin_matrix[(row, col)];
-                        }
+                if is_safe_sub_row && basin_matrix[(row - 1, col)] != Some(0) {
+                    if basin_matrix[(row - 1, col)] > basin_matrix[(row, col)] {
+                        basin_matrix[(row, col)] = basin_matrix[(row - 1, col)]
+                    } else {
+                        basin_matrix[(row - 1, col)] = basin_matrix[(row, col)];
                     }
                 }
                 // d
-                if is_safe_sub_col {
-                    if basin_matrix[(row, col - 1)] != Some(0) {
-                        if basin_matrix[(row, col - 1)] > basin_matrix[(row, col)] {
-                            basin_matrix[(row, col)] = basin_matrix[(row, col - 1)];
-                        } else {
-                            basin_matrix[(row, col - 1)] = basin_matrix[(row, col)];
-                        }
+                if is_safe_sub_col && basin_matrix[(row, col - 1)] != Some(0) {
+                    if basin_matrix[(row, col - 1)] > basin_matrix[(row, col)] {
+                        basin_matrix[(row, col)] = basin_matrix[(row, col - 1)];
+                    } else {
+                        basin_matrix[(row, col - 1)] = basin_matrix[(row, col)];
                     }
                 }
                 // e
-                if is_safe_add_col {
-                    if basin_matrix[(row, col + 1)] != Some(0) {
-                        if basin_matrix[(row, col + 1)] > basin_matrix[(row, col)] {
-                            basin_matrix[(row, col)] = basin_matrix[(row, col + 1)];
-                        } else {
-                            basin_matrix[(row, col + 1)] = basin_matrix[(row, col)];
-                        }
+                if is_safe_add_col && basin_matrix[(row, col + 1)] != Some(0) {
+                    if basin_matrix[(row, col + 1)] > basin_matrix[(row, col)] {
+                        basin_matrix[(row, col)] = basin_matrix[(row, col + 1)];
+                    } else {
+                        basin_matrix[(row, col + 1)] = basin_matrix[(row, col)];
                     }
                 }
                 // g
-                if is_safe_add_row {
-                    if basin_matrix[(row + 1, col)] != Some(0) {
-                        if basin_matrix[(row + 1, col)] > basin_matrix[(row, col)] {
-                            basin_matrix[(row, col)] = basin_matrix[(row + 1, col)];
-                        } else {
-                            basin_matrix[(row + 1, col)] = basin_matrix[(row, col)];
-                        }
+                if is_safe_add_row && basin_matrix[(row + 1, col)] != Some(0) {
+                    if basin_matrix[(row + 1, col)] > basin_matrix[(row, col)] {
+                        basin_matrix[(row, col)] = basin_matrix[(row + 1, col)];
+                    } else {
+                        basin_matrix[(row + 1, col)] = basin_matrix[(row, col)];
                     }
                 }
             }
@@ -193,10 +211,22 @@ fn count_basins(basin_matrix: &BasinMatrix) -> HashMap<usize, usize> {
     basin_map
 }
 
-fn part2(matrix: &Matrix) -> usize {
+// Basins that touch were labeled apart, fill_larger_number joins them up
+fn trace_merge(basin_matrix: &BasinMatrix, basins: &HashMap<usize, usize>) {
+    trace::emit(|| {
+        Event::new(
+            "merge",
+            format!("Merging touching labels leaves {} basins", basins.len()),
+        )
+        .with("basins", basins.len())
+        .with("labels", snapshot(basin_matrix))
+    });
+}
+
+// Every cell labeled with its basin, and the nines with 0
+fn label_basins(matrix: &Matrix) -> BasinMatrix {
     let mut basin_matrix: BasinMatrix =
         BasinMatrix::from_element(matrix.nrows(), matrix.ncols(), None);
-    // println!("{:?} {:?} {:?} {:?}", matrix.nrows(), matrix.ncols(), basin_matrix.nrows(), basin_matrix.ncols());
 
     let mut basin_counter: usize = 1;
     basin_matrix[(0, 0)] = Some(basin_counter);
@@ -217,7 +247,7 @@ fn part2(matrix: &Matrix) -> usize {
         let mut index: (usize, usize) = (0, 0);
         for row in 0..matrix.nrows() {
             for col in 0..matrix.ncols() {
-                if basin_matrix[(row, col)] == None {
+                if basin_matrix[(row, col)].is_none() {
                     index = (row, col);
                     break;
                 }
@@ -231,43 +261,215 @@ fn part2(matrix: &Matrix) -> usize {
     let mut before = count_basins(&basin_matrix);
     fill_larger_number(&mut basin_matrix);
     let mut after = count_basins(&basin_matrix);
+    trace_merge(&basin_matrix, &after);
     // Run this until the hashmap doesn't change
     while before != after {
         before = after.clone();
         fill_larger_number(&mut basin_matrix);
         after = count_basins(&basin_matrix);
+        trace_merge(&basin_matrix, &after);
     }
+    basin_matrix
+}
 
-    let mut sizes: Vec<usize> = after.values().map(|x| *x).collect();
+// The size of every basin, largest first
+pub fn basin_sizes(matrix: &Matrix) -> Vec<usize> {
+    let mut sizes: Vec<usize> = count_basins(&label_basins(matrix))
+        .values()
+        .copied()
+        .collect();
     sizes.sort();
-    sizes.iter().rev().take(3).fold(1, |acc, x| acc * *x)
+    sizes.reverse();
+    trace::emit(|| {
+        Event::new(
+            "basins",
+            format!(
+                "The {}x{} map has {} basins, the largest are {:?}",
+                matrix.nrows(),
+                matrix.ncols(),
+                sizes.len(),
+                &sizes[..sizes.len().min(3)]
+            ),
+        )
+        .with("basins", sizes.len())
+    });
+    sizes
 }
 
-fn display(matrix: &BasinMatrix) {
-    for row in matrix.nrows() - 10..matrix.nrows() {
-        for col in 0..11 {
-            if let Some(v) = matrix[(row, col)] {
-                print!("{:7}", v);
+pub fn part2(matrix: &Matrix) -> usize {
+    basin_sizes(matrix).iter().take(3).product()
+}
+
+// The heights, and the basins in a colour each
+pub fn pictures(matrix: &Matrix) -> Vec<Picture> {
+    let heights: Grid = matrix
+        .row_iter()
+        .map(|row| row.iter().map(|h| Some(*h as i64)).collect())
+        .collect();
+    vec![
+        Picture {
+            name: "heights",
+            grid: heights,
+            palette: Palette::Gray,
+        },
+        Picture {
+            name: "basins",
+            grid: snapshot(&label_basins(matrix)),
+            palette: Palette::Labels,
+        },
+    ]
+}
+
+// Roughly a quarter of the heights are 9s, which split the map into basins
+fn generate(rng: &mut Rng, size: usize) -> String {
+    let columns = size.clamp(5, 100);
+    (0..size.max(1))
+        .map(|_| {
+            let row: String = (0..columns)
+                .map(|_| {
+                    if rng.chance(25) {
+                        '9'
+                    } else {
+                        char::from(b'0' + rng.below(9) as u8)
+                    }
+                })
+                .collect();
+            row + "\n"
+        })
+        .collect()
+}
+
+pub struct Day9;
+
+impl Solution for Day9 {
+    type Input = Matrix;
+
+    fn parse(&self, input: &str) -> Result<Matrix> {
+        build_matrix(parse_all(input, parse_lines)?)
+    }
+
+    fn part1(&self, input: &Matrix) -> Result<Answer> {
+        Ok(part1(input).into())
+    }
+
+    // See answers.txt for the guesses that came before the correct answer
+    fn part2(&self, input: &Matrix) -> Result<Answer> {
+        Ok(part2(input).into())
+    }
+
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        Some(generate(rng, size))
+    }
+
+    fn pictures(&self, input: &Matrix) -> Vec<Picture> {
+        pictures(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::property::{assert_property, shrink_vec};
+
+    #[test]
+    fn parses_rows() {
+        assert_eq!(
+            parse_line("2199943210").map(|r| r.1),
+            Ok(vec![2, 1, 9, 9, 9, 4, 3, 2, 1, 0])
+        );
+    }
+
+    #[test]
+    fn rejects_ragged_rows() {
+        let rows = parse_all("219\n39\n", parse_lines).unwrap();
+        assert!(matches!(build_matrix(rows), Err(Error::Validation(_))));
+    }
+
+    // Rectangular heightmaps, about a quarter 9s
+    fn heightmap(rng: &mut Rng, size: usize) -> Vec<Vec<usize>> {
+        let columns = rng.between(1, size);
+        (0..rng.between(1, size))
+            .map(|_| {
+                (0..columns)
+                    .map(|_| if rng.chance(25) { 9 } else { rng.below(9) })
+                    .collect()
+            })
+            .collect()
+    }
+
+    // Drop a row or a column, or flatten a height to 0
+    fn shrink_heightmap(rows: &[Vec<usize>]) -> Vec<Vec<Vec<usize>>> {
+        let mut candidates: Vec<Vec<Vec<usize>>> = shrink_vec(rows)
+            .into_iter()
+            .filter(|rows| !rows.is_empty())
+            .collect();
+        for col in 0..rows[0].len() {
+            if rows[0].len() > 1 {
+                candidates.push(
+                    rows.iter()
+                        .map(|row| [&row[..col], &row[col + 1..]].concat())
+                        .collect(),
+                );
+            }
+        }
+        for (idx, row) in rows.iter().enumerate() {
+            for (col, height) in row.iter().enumerate() {
+                if *height != 0 {
+                    let mut candidate = rows.to_vec();
+                    candidate[idx][col] = 0;
+                    candidates.push(candidate);
+                }
             }
         }
-        println!();
+        candidates
     }
-}
 
-pub fn run() {
-    assert_eq!(
-        parse_line("2199943210").unwrap().1,
-        [2, 1, 9, 9, 9, 4, 3, 2, 1, 0]
-    );
-
-    let test_str = include_str!("../inputs/day9.test");
-    let input_str = include_str!("../inputs/day9");
-    let test = parse_lines(test_str).unwrap().1;
-    let input = parse_lines(input_str).unwrap().1;
-    println!("Part 1 {:?}", part1(&input));
-    // 54756 is too low
-    // 79376 is too low
-    // 893564 is too low
-    // 1123524 is correct :)
-    println!("{:?}", part2(&input));
+    #[test]
+    fn basins_cover_every_height_below_nine() {
+        assert_property(
+            "basin sizes sum to the number of heights below 9",
+            heightmap,
+            |rows| shrink_heightmap(rows),
+            |rows| {
+                let matrix = build_matrix(rows.clone()).map_err(|e| e.to_string())?;
+                let sizes = basin_sizes(&matrix);
+                let below_nine = matrix.iter().filter(|height| **height != 9).count();
+                if sizes.iter().sum::<usize>() == below_nine {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "basins {:?} but {} heights below 9",
+                        sizes, below_nine
+                    ))
+                }
+            },
+        );
+    }
+
+    #[test]
+    fn reads_the_matrix_by_row_and_column() {
+        let matrix = build_matrix(parse_all("219\n398\n", parse_lines).unwrap()).unwrap();
+        assert_eq!(matrix[(0, 2)], 9);
+        assert_eq!(matrix[(1, 0)], 3);
+    }
+
+    #[test]
+    fn pictures_the_basins_a_colour_each() {
+        let day9_test: &str = include_str!("../inputs/day9.test");
+        let matrix = build_matrix(parse_all(day9_test, parse_lines).unwrap()).unwrap();
+        let pictures = pictures(&matrix);
+        assert_eq!(pictures[0].grid[0][..3], [Some(2), Some(1), Some(9)]);
+        let mut labels: Vec<i64> = pictures[1]
+            .grid
+            .iter()
+            .flatten()
+            .flatten()
+            .copied()
+            .collect();
+        labels.sort();
+        labels.dedup();
+        // The nines, then one label per basin
+        assert_eq!(labels.len(), 5);
+        assert_eq!(labels[0], 0);
+    }
 }
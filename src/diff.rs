@@ -0,0 +1,169 @@
+// Runs every implementation of a part on the same input. When they disagree,
+// the input is cut down to the fewest leading lines that still shows it.
+use super::lib::{Part, Result};
+use super::solution::{Answer, Runner};
+
+// Errors aren't comparable, so they are compared by their message
+pub type Outcome = (&'static str, std::result::Result<Answer, String>);
+
+pub struct Disagreement {
+    pub lines: usize,
+    pub prefix: String,
+    pub outcomes: Vec<Outcome>,
+}
+
+pub struct Comparison {
+    pub part: Part,
+    pub outcomes: Vec<Outcome>,
+    pub disagreement: Option<Disagreement>,
+}
+
+fn solve_each(solution: &dyn Runner, input: &str, part: Part) -> Result<Vec<Outcome>> {
+    Ok(solution
+        .solve_each(input, part)?
+        .into_iter()
+        .map(|(name, answer)| (name, answer.map_err(|e| e.to_string())))
+        .collect())
+}
+
+fn agree(outcomes: &[Outcome]) -> bool {
+    outcomes.windows(2).all(|pair| pair[0].1 == pair[1].1)
+}
+
+// The first 1, 2, 3... lines of the input, each with its line count. A last
+// line without a line break is a line too.
+fn prefixes(input: &str) -> impl Iterator<Item = (usize, &str)> {
+    let unterminated = (!input.ends_with('\n')).then_some(input);
+    input
+        .match_indices('\n')
+        .map(|(idx, _)| &input[..idx])
+        .chain(unterminated)
+        .enumerate()
+        .map(|(idx, prefix)| (idx + 1, prefix))
+}
+
+pub fn compare(solution: &dyn Runner, input: &str, part: Part) -> Result<Comparison> {
+    let outcomes = solve_each(solution, input, part)?;
+    if agree(&outcomes) {
+        return Ok(Comparison {
+            part,
+            outcomes,
+            disagreement: None,
+        });
+    }
+
+    // Agreement isn't monotonic, so every prefix has to be tried in order.
+    // Prefixes that don't parse, e.g. half a bingo board, can't show anything.
+    let disagreement = prefixes(input).find_map(|(lines, prefix)| {
+        let outcomes = solve_each(solution, prefix, part).ok()?;
+        if agree(&outcomes) {
+            None
+        } else {
+            Some(Disagreement {
+                lines,
+                prefix: prefix.to_string(),
+                outcomes,
+            })
+        }
+    });
+    Ok(Comparison {
+        part,
+        outcomes,
+        disagreement,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lib::Error;
+    use crate::solution::{Alternative, Solution};
+
+    // Counts lines, but the alternative can't count past three
+    struct Lines;
+
+    impl Solution for Lines {
+        type Input = Vec<String>;
+
+        fn parse(&self, input: &str) -> Result<Vec<String>> {
+            if input.contains('x') {
+                return Err(Error::Validation("no x".to_string()));
+            }
+            Ok(input.lines().map(String::from).collect())
+        }
+
+        fn part1(&self, input: &Vec<String>) -> Result<Answer> {
+            Ok(input.len().into())
+        }
+
+        fn alternatives(&self) -> Vec<Alternative<Vec<String>>> {
+            vec![Alternative {
+                name: "capped",
+                part: Part::One,
+                solve: |input| Ok(input.len().min(3).into()),
+            }]
+        }
+    }
+
+    #[test]
+    fn lists_every_prefix() {
+        let prefixes: Vec<(usize, &str)> = prefixes("a\nb\nc").collect();
+        assert_eq!(prefixes, vec![(1, "a"), (2, "a\nb"), (3, "a\nb\nc")]);
+        let terminated: Vec<(usize, &str)> = super::prefixes("a\nb\n").collect();
+        assert_eq!(terminated, vec![(1, "a"), (2, "a\nb")]);
+    }
+
+    #[test]
+    fn finds_the_shortest_disagreeing_prefix() {
+        let comparison = compare(&Lines, "a\nb\nc\nd\ne\n", Part::One).unwrap();
+        assert_eq!(
+            comparison.outcomes,
+            vec![
+                ("solution", Ok(Answer::Number(5))),
+                ("capped", Ok(Answer::Number(3)))
+            ]
+        );
+        let disagreement = comparison.disagreement.unwrap();
+        assert_eq!(disagreement.lines, 4);
+        assert_eq!(disagreement.prefix, "a\nb\nc\nd");
+    }
+
+    #[test]
+    fn skips_prefixes_that_do_not_parse() {
+        assert!(compare(&Lines, "x", Part::One).is_err());
+        let comparison = compare(&Lines, "a\nb\nc", Part::One).unwrap();
+        assert!(comparison.disagreement.is_none());
+    }
+
+    #[test]
+    fn a_panic_is_a_disagreement() {
+        struct Fragile;
+        impl Solution for Fragile {
+            type Input = Vec<String>;
+            fn parse(&self, input: &str) -> Result<Vec<String>> {
+                Lines.parse(input)
+            }
+            fn part1(&self, input: &Vec<String>) -> Result<Answer> {
+                Ok(input.len().into())
+            }
+            fn alternatives(&self) -> Vec<Alternative<Vec<String>>> {
+                vec![Alternative {
+                    name: "third",
+                    part: Part::One,
+                    solve: |input| Ok(input[2].len().into()),
+                }]
+            }
+        }
+        let comparison = compare(&Fragile, "a\nb\nc", Part::One).unwrap();
+        let disagreement = comparison.disagreement.unwrap();
+        assert_eq!(disagreement.lines, 1);
+        assert!(matches!(&disagreement.outcomes[1].1, Err(e) if e.contains("panicked")));
+    }
+
+    #[test]
+    fn unimplemented_parts_agree() {
+        let comparison = compare(&Lines, "a", Part::Two).unwrap();
+        assert_eq!(comparison.outcomes.len(), 1);
+        assert!(comparison.disagreement.is_none());
+    }
+}
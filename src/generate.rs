@@ -0,0 +1,117 @@
+// Random puzzle inputs, for stress tests and benchmarks that need more than the
+// committed inputs. Every day's Solution knows how to write its own, see
+// Solution::generate. The same seed and size always give the same input.
+//
+// There's no rand in the dependencies, and a SplitMix64 is plenty for this.
+pub const DEFAULT_SIZE: usize = 1000;
+
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    // SplitMix64, see https://prng.di.unimi.it/splitmix64.c
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // A number in 0..n. The modulo bias doesn't matter for puzzle inputs.
+    pub fn below(&mut self, n: usize) -> usize {
+        assert!(n > 0, "Rng::below(0)");
+        (self.next_u64() % n as u64) as usize
+    }
+
+    // A number in low..=high
+    pub fn between(&mut self, low: usize, high: usize) -> usize {
+        low + self.below(high - low + 1)
+    }
+
+    // True with the given probability, in percent
+    pub fn chance(&mut self, percent: usize) -> bool {
+        self.below(100) < percent
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+
+    // Fisher-Yates
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for idx in (1..items.len()).rev() {
+            items.swap(idx, self.below(idx + 1));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lib::{Error, Part};
+    use crate::solution::REGISTRY;
+
+    #[test]
+    fn the_same_seed_gives_the_same_numbers() {
+        let numbers = |seed| {
+            let mut rng = Rng::new(seed);
+            (0..10).map(|_| rng.below(1000)).collect::<Vec<usize>>()
+        };
+        assert_eq!(numbers(7), numbers(7));
+        assert_ne!(numbers(7), numbers(8));
+    }
+
+    #[test]
+    fn stays_in_range_and_shuffles_everything() {
+        let mut rng = Rng::new(1);
+        assert!((0..1000).all(|_| (3..=5).contains(&rng.between(3, 5))));
+        let mut items: Vec<usize> = (0..50).collect();
+        rng.shuffle(&mut items);
+        assert_ne!(items, (0..50).collect::<Vec<usize>>());
+        items.sort();
+        assert_eq!(items, (0..50).collect::<Vec<usize>>());
+    }
+
+    // Every generated input has to parse, and every implemented part has to
+    // find an answer in it
+    #[test]
+    fn every_day_generates_solvable_inputs() {
+        for day in REGISTRY {
+            for (seed, size) in [(1, 1), (2, 5), (3, 40)] {
+                let input = day
+                    .solution
+                    .generate(&mut Rng::new(seed), size)
+                    .unwrap_or_else(|| panic!("Day {} has no generator", day.number));
+                let run = day
+                    .solution
+                    .run(&input, &[Part::One, Part::Two])
+                    .unwrap_or_else(|e| panic!("Day {}: {}\n{}", day.number, e, input));
+                for part in run.parts {
+                    assert!(
+                        matches!(part.answer, Ok(_) | Err(Error::NotImplemented)),
+                        "Day {}, size {}: {:?}\n{}",
+                        day.number,
+                        size,
+                        part.answer.err(),
+                        input
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn generators_are_deterministic() {
+        for day in REGISTRY {
+            let input = |seed| day.solution.generate(&mut Rng::new(seed), 20);
+            assert_eq!(input(5), input(5), "Day {}", day.number);
+            assert_ne!(input(5), input(6), "Day {}", day.number);
+        }
+    }
+}
@@ -0,0 +1,303 @@
+// Writes grids out as images. PPM and PGM are little more than a header and the
+// pixels. PNG needs its pixels deflated, which is done with stored blocks only,
+// so nothing is compressed: the file is as large as the PPM, but any viewer
+// opens it and there's nothing to depend on beyond std.
+use super::trace::Grid;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+pub type Rgb = [u8; 3];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Ppm,
+    Pgm,
+    Png,
+}
+
+impl Format {
+    pub fn parse(value: &str) -> Option<Format> {
+        match value {
+            "ppm" => Some(Format::Ppm),
+            "pgm" => Some(Format::Pgm),
+            "png" => Some(Format::Png),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Ppm => "ppm",
+            Format::Pgm => "pgm",
+            Format::Png => "png",
+        }
+    }
+}
+
+// How the cells of a picture become colours. Empty cells are always black.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Palette {
+    // Brighter for higher values, e.g. heights
+    Gray,
+    // Black through red and yellow to white, e.g. how often something happened
+    Heat,
+    // A colour of its own for every label, and dark grey for 0, e.g. basins
+    Labels,
+}
+
+// A grid from a solver, named for the file it's written to
+#[derive(Debug, Clone, PartialEq)]
+pub struct Picture {
+    pub name: &'static str,
+    pub grid: Grid,
+    pub palette: Palette,
+}
+
+const DARK: Rgb = [40, 40, 40];
+const GOLDEN_RATIO: f64 = 0.618_033_988_749_895;
+
+fn gray(value: i64, largest: i64) -> Rgb {
+    let level = (value.clamp(0, largest) * 255 / largest) as u8;
+    [level; 3]
+}
+
+fn heat(value: i64, largest: i64) -> Rgb {
+    let t = value.clamp(0, largest) * 765 / largest;
+    [
+        t.min(255) as u8,
+        (t - 255).clamp(0, 255) as u8,
+        (t - 510).clamp(0, 255) as u8,
+    ]
+}
+
+// Stepping the hue by the golden ratio keeps neighbouring labels apart
+fn label(value: i64) -> Rgb {
+    if value == 0 {
+        return DARK;
+    }
+    let hue = (value as f64 * GOLDEN_RATIO).fract().abs() * 6.0;
+    let (value, saturation) = (0.95, 0.65);
+    let channel = |n: f64| {
+        let k = (n + hue) % 6.0;
+        let rgb = value - value * saturation * k.min(4.0 - k).clamp(0.0, 1.0);
+        (rgb * 255.0).round() as u8
+    };
+    [channel(5.0), channel(3.0), channel(1.0)]
+}
+
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pixels: Vec<Rgb>,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize) -> Image {
+        Image {
+            width,
+            height,
+            pixels: vec![[0; 3]; width * height],
+        }
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Rgb {
+        self.pixels[y * self.width + x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, rgb: Rgb) {
+        self.pixels[y * self.width + x] = rgb;
+    }
+
+    // Every cell as a square of scale by scale pixels. Rows can be ragged, the
+    // image is as wide as the longest one.
+    pub fn draw(picture: &Picture, scale: usize) -> Image {
+        let scale = scale.max(1);
+        let width = picture.grid.iter().map(Vec::len).max().unwrap_or(0);
+        let largest = picture.grid.iter().flatten().flatten().max().copied();
+        let largest = largest.unwrap_or(0).max(1);
+        let mut image = Image::new(width * scale, picture.grid.len() * scale);
+        for (row, cells) in picture.grid.iter().enumerate() {
+            for (col, cell) in cells.iter().enumerate() {
+                let Some(value) = cell else {
+                    continue;
+                };
+                let rgb = match picture.palette {
+                    Palette::Gray => gray(*value, largest),
+                    Palette::Heat => heat(*value, largest),
+                    Palette::Labels => label(*value),
+                };
+                for y in row * scale..(row + 1) * scale {
+                    for x in col * scale..(col + 1) * scale {
+                        image.set(x, y, rgb);
+                    }
+                }
+            }
+        }
+        image
+    }
+
+    pub fn encode(&self, format: Format) -> Vec<u8> {
+        match format {
+            Format::Ppm => self.ppm(),
+            Format::Pgm => self.pgm(),
+            Format::Png => self.png(),
+        }
+    }
+
+    pub fn save(&self, path: &Path, format: Format) -> io::Result<()> {
+        fs::write(path, self.encode(format))
+    }
+
+    fn ppm(&self) -> Vec<u8> {
+        let mut bytes = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        bytes.extend(self.pixels.iter().flatten());
+        bytes
+    }
+
+    // Colours become their luminance
+    fn pgm(&self) -> Vec<u8> {
+        let mut bytes = format!("P5\n{} {}\n255\n", self.width, self.height).into_bytes();
+        bytes.extend(
+            self.pixels.iter().map(|[r, g, b]| {
+                ((299 * *r as u32 + 587 * *g as u32 + 114 * *b as u32) / 1000) as u8
+            }),
+        );
+        bytes
+    }
+
+    // 8 bit RGB, with no filtering on any row
+    fn png(&self) -> Vec<u8> {
+        let mut header: Vec<u8> = Vec::new();
+        header.extend((self.width as u32).to_be_bytes());
+        header.extend((self.height as u32).to_be_bytes());
+        // Bit depth, colour type, compression, filter and interlace method
+        header.extend([8, 2, 0, 0, 0]);
+
+        let mut rows: Vec<u8> = Vec::with_capacity((self.width * 3 + 1) * self.height);
+        for row in self.pixels.chunks(self.width.max(1)) {
+            rows.push(0);
+            rows.extend(row.iter().flatten());
+        }
+
+        let mut bytes: Vec<u8> = vec![0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+        chunk(&mut bytes, b"IHDR", &header);
+        chunk(&mut bytes, b"IDAT", &zlib_stored(&rows));
+        chunk(&mut bytes, b"IEND", &[]);
+        bytes
+    }
+}
+
+fn chunk(bytes: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    bytes.extend((data.len() as u32).to_be_bytes());
+    let start = bytes.len();
+    bytes.extend(kind);
+    bytes.extend(data);
+    let crc = crc32(&bytes[start..]);
+    bytes.extend(crc.to_be_bytes());
+}
+
+// A zlib stream of deflate blocks that are stored as they are, at most 65535
+// bytes each
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    const MAX_BLOCK: usize = 0xffff;
+    let mut bytes: Vec<u8> = vec![0x78, 0x01];
+    let blocks = data.len().div_ceil(MAX_BLOCK).max(1);
+    for idx in 0..blocks {
+        let block = &data[idx * MAX_BLOCK..((idx + 1) * MAX_BLOCK).min(data.len())];
+        bytes.push((idx + 1 == blocks) as u8);
+        bytes.extend((block.len() as u16).to_le_bytes());
+        bytes.extend((!(block.len() as u16)).to_le_bytes());
+        bytes.extend(block);
+    }
+    bytes.extend(adler32(data).to_be_bytes());
+    bytes
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut table = [0u32; 256];
+    for (n, entry) in table.iter_mut().enumerate() {
+        *entry = (0..8).fold(n as u32, |c, _| match c & 1 {
+            1 => 0xedb8_8320 ^ (c >> 1),
+            _ => c >> 1,
+        });
+    }
+    !data.iter().fold(!0u32, |crc, byte| {
+        table[((crc ^ *byte as u32) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (a, b) = data.iter().fold((1u32, 0u32), |(a, b), byte| {
+        let a = (a + *byte as u32) % 65521;
+        (a, (b + a) % 65521)
+    });
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn picture(grid: Grid, palette: Palette) -> Picture {
+        Picture {
+            name: "test",
+            grid,
+            palette,
+        }
+    }
+
+    #[test]
+    fn checksums_match_known_values() {
+        assert_eq!(crc32(b"IEND"), 0xae42_6082);
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+    }
+
+    #[test]
+    fn draws_cells_as_squares() {
+        let grid = vec![vec![Some(0), Some(9)], vec![None]];
+        let image = Image::draw(&picture(grid, Palette::Gray), 2);
+        assert_eq!((image.width, image.height), (4, 4));
+        assert_eq!(image.get(0, 0), [0; 3]);
+        assert_eq!(image.get(3, 1), [255; 3]);
+        assert_eq!(image.get(3, 3), [0; 3]);
+
+        let heat = Image::draw(&picture(vec![vec![Some(1), Some(2)]], Palette::Heat), 1);
+        assert_eq!(heat.get(0, 0), [255, 127, 0]);
+        assert_eq!(heat.get(1, 0), [255, 255, 255]);
+        assert_ne!(label(1), label(2));
+        assert_eq!(label(0), DARK);
+    }
+
+    #[test]
+    fn writes_netpbm_headers() {
+        let mut image = Image::new(2, 1);
+        image.set(1, 0, [255, 0, 0]);
+        assert_eq!(image.encode(Format::Ppm), b"P6\n2 1\n255\n\0\0\0\xff\0\0");
+        assert_eq!(image.encode(Format::Pgm), b"P5\n2 1\n255\n\0\x4c");
+    }
+
+    #[test]
+    fn writes_a_png_of_stored_blocks() {
+        let image = Image::new(200, 200);
+        let png = image.encode(Format::Png);
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        assert_eq!(&png[12..16], b"IHDR");
+        assert_eq!(&png[16..24], [0, 0, 0, 200, 0, 0, 0, 200]);
+        assert!(png.ends_with(b"IEND\xae\x42\x60\x82"));
+
+        // 200 rows of a filter byte and 600 bytes of pixels need two blocks
+        let deflated = zlib_stored(&vec![7; 120_200]);
+        assert_eq!(deflated[2], 0);
+        assert_eq!(deflated[3..5], 0xffffu16.to_le_bytes());
+        let second = 2 + 5 + 0xffff;
+        assert_eq!(deflated[second], 1);
+        assert_eq!(deflated[second + 1..second + 3], 54665u16.to_le_bytes());
+        assert_eq!(deflated.len(), 2 + 2 * 5 + 120_200 + 4);
+        assert_eq!(
+            zlib_stored(&[]),
+            [0x78, 0x01, 1, 0, 0, 0xff, 0xff, 0, 0, 0, 1]
+        );
+    }
+}
@@ -0,0 +1,69 @@
+// Inputs are read at runtime so we can swap in our own without recompiling.
+//
+// Every day follows the same naming scheme inside the input directory:
+//
+//     day<N>       the puzzle input
+//     day<N>.test  the example from the puzzle description
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+pub const DEFAULT_DIR: &str = "inputs";
+
+// Overrides DEFAULT_DIR when --input-dir isn't given
+pub const DIR_VARIABLE: &str = "AOC_INPUT_DIR";
+
+pub fn input_dir(flag: Option<&str>) -> PathBuf {
+    match (flag, env::var(DIR_VARIABLE)) {
+        (Some(dir), _) => PathBuf::from(dir),
+        (None, Ok(dir)) => PathBuf::from(dir),
+        (None, Err(_)) => PathBuf::from(DEFAULT_DIR),
+    }
+}
+
+pub fn input_path(dir: &Path, day: u8) -> PathBuf {
+    dir.join(format!("day{}", day))
+}
+
+pub fn sample_path(dir: &Path, day: u8) -> PathBuf {
+    dir.join(format!("day{}.test", day))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Source {
+    File(PathBuf),
+    Stdin,
+}
+
+impl Source {
+    // "-" means stdin, like most command line tools
+    pub fn from_arg(arg: &str) -> Source {
+        if arg == "-" {
+            Source::Stdin
+        } else {
+            Source::File(PathBuf::from(arg))
+        }
+    }
+
+    pub fn load(&self) -> io::Result<String> {
+        match self {
+            Source::File(path) => fs::read_to_string(path),
+            Source::Stdin => {
+                let mut buffer = String::new();
+                io::stdin().read_to_string(&mut buffer)?;
+                Ok(buffer)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::File(path) => write!(f, "{}", path.display()),
+            Source::Stdin => write!(f, "<stdin>"),
+        }
+    }
+}
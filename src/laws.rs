@@ -0,0 +1,173 @@
+// Checks the laws every Semigroup and Monoid has to obey, with property tests
+// over random triples of values:
+//
+//     associativity    (a <> b) <> c == a <> (b <> c)
+//     left identity    mempty <> a == a
+//     right identity   a <> mempty == a
+//
+// where <> is mappend. Folds rely on these to give the same answer however
+// they're grouped, so an instance that breaks them is only right by accident.
+//
+//     assert_laws("Sum<i64>", |rng, size| Sum(rng.below(size) as i64));
+//
+// A failing triple is shrunk by swapping its values for mempty, as long as a
+// law still breaks.
+use super::generate::Rng;
+use super::lib::Monoid;
+use super::property::{check, Config, Failure};
+use std::fmt::Debug;
+
+pub type Triple<M> = (M, M, M);
+
+fn laws<M: Monoid + Clone + PartialEq + Debug>((a, b, c): &Triple<M>) -> Result<(), String> {
+    let left = M::mappend(M::mappend(a.clone(), b.clone()), c.clone());
+    let right = M::mappend(a.clone(), M::mappend(b.clone(), c.clone()));
+    if left != right {
+        return Err(format!(
+            "Not associative: (a <> b) <> c is {:?}, but a <> (b <> c) is {:?}",
+            left, right
+        ));
+    }
+    for (name, value) in [("a", a), ("b", b), ("c", c)] {
+        let appended = M::mappend(M::mempty(), value.clone());
+        if appended != *value {
+            return Err(format!(
+                "No left identity: mempty <> {} is {:?}",
+                name, appended
+            ));
+        }
+        let appended = M::mappend(value.clone(), M::mempty());
+        if appended != *value {
+            return Err(format!(
+                "No right identity: {} <> mempty is {:?}",
+                name, appended
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn shrink_triple<M: Monoid + Clone + PartialEq>((a, b, c): &Triple<M>) -> Vec<Triple<M>> {
+    let empty = M::mempty();
+    let mut candidates: Vec<Triple<M>> = Vec::new();
+    if *a != empty {
+        candidates.push((empty.clone(), b.clone(), c.clone()));
+    }
+    if *b != empty {
+        candidates.push((a.clone(), empty.clone(), c.clone()));
+    }
+    if *c != empty {
+        candidates.push((a.clone(), b.clone(), empty));
+    }
+    candidates
+}
+
+pub fn check_laws<M: Monoid + Clone + PartialEq + Debug>(
+    config: &Config,
+    generate: impl Fn(&mut Rng, usize) -> M,
+) -> Result<(), Failure<Triple<M>>> {
+    check(
+        config,
+        |rng, size| {
+            (
+                generate(rng, size),
+                generate(rng, size),
+                generate(rng, size),
+            )
+        },
+        shrink_triple,
+        laws,
+    )
+}
+
+// Panics with the failing triple when a law doesn't hold
+pub fn assert_laws<M: Monoid + Clone + PartialEq + Debug>(
+    name: &str,
+    generate: impl Fn(&mut Rng, usize) -> M,
+) {
+    let config = Config::default();
+    if let Err(failure) = check_laws(&config, generate) {
+        let (a, b, c) = failure.minimal;
+        panic!(
+            "{} breaks the monoid laws, case {} with seed {}:\n\
+             a = {:?}\nb = {:?}\nc = {:?}\n{}",
+            name, failure.case, config.seed, a, b, c, failure.message
+        );
+    }
+}
+
+// A number in -size..=size, for instances over signed integers
+pub fn signed(rng: &mut Rng, size: usize) -> i64 {
+    rng.below(2 * size + 1) as i64 - size as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lib::Semigroup;
+
+    // Subtraction, which is neither associative nor has a left identity
+    #[derive(Debug, Clone, PartialEq)]
+    struct Difference(i64);
+
+    impl Semigroup for Difference {
+        fn mappend(fst: Self, snd: Self) -> Self {
+            Difference(fst.0 - snd.0)
+        }
+    }
+
+    impl Monoid for Difference {
+        fn mempty() -> Self {
+            Difference(0)
+        }
+    }
+
+    // Associative, but 0 only leaves values of 0 or more alone
+    #[derive(Debug, Clone, PartialEq)]
+    struct Larger(i64);
+
+    impl Semigroup for Larger {
+        fn mappend(fst: Self, snd: Self) -> Self {
+            Larger(fst.0.max(snd.0))
+        }
+    }
+
+    impl Monoid for Larger {
+        fn mempty() -> Self {
+            Larger(0)
+        }
+    }
+
+    #[test]
+    fn reports_a_minimal_triple_for_a_broken_law() {
+        let failure = check_laws(&Config::default(), |rng, size| {
+            Difference(signed(rng, size))
+        })
+        .unwrap_err();
+        let (a, b, c) = &failure.minimal;
+        assert_eq!((a, b), (&Difference(0), &Difference(0)));
+        assert_ne!(*c, Difference(0));
+        assert!(failure.message.starts_with("Not associative"));
+    }
+
+    #[test]
+    fn finds_a_missing_identity() {
+        let failure =
+            check_laws(&Config::default(), |rng, size| Larger(signed(rng, size))).unwrap_err();
+        assert!(
+            failure.message.starts_with("No left identity"),
+            "{}",
+            failure.message
+        );
+        assert!(check_laws(&Config::default(), |rng, size| Larger(
+            rng.below(size) as i64
+        ))
+        .is_ok());
+    }
+
+    #[test]
+    #[should_panic(expected = "a = Difference(")]
+    fn panics_with_the_triple() {
+        assert_laws("Difference", |rng, size| Difference(signed(rng, size)));
+    }
+}
@@ -1,48 +1,787 @@
-use nom::{error::VerboseError, IResult};
-use std::fs::File;
-use std::io::{self, BufRead, BufReader, Lines, Result as IOResult};
-use std::path::Path;
+use super::pool;
+use nom::{
+    error::{ErrorKind, VerboseError, VerboseErrorKind},
+    Err as NomErr, IResult, Offset,
+};
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+use std::io;
+use std::ops::{Add, Mul};
+use std::panic;
+use std::thread;
 
 pub type Res<T, U> = IResult<T, U, VerboseError<T>>;
 
-pub fn read_lines<P>(filename: P) -> IOResult<Lines<BufReader<File>>>
-// This is similar to a constraint in Haskell
-where
-    P: AsRef<Path>,
-{
-    let file = File::open(filename)?;
-    Ok(io::BufReader::new(file).lines())
-}
-
-pub fn read_file_and_parse_lines<P, T>(p: P, f: fn(&String) -> Option<T>) -> Vec<T>
-where
-    P: AsRef<Path>,
-{
-    match read_lines(p) {
-        Ok(lines) => {
-            let mut result: Vec<T> = Vec::new();
-            for line in lines {
-                // We're not really concerned about being unable to read a line...
-                let s = line.unwrap();
-
-                match f(&s) {
-                    Some(i) => result.push(i),
-                    None => panic!(
-                        "Unable to read the following unsigned integer, got: {:?}",
-                        s
-                    ),
-                };
+// Where parsing stopped, in terms a human can find in the input
+#[derive(Debug, PartialEq)]
+pub struct ParseError {
+    // Byte offset into the whole input
+    pub offset: usize,
+    // Both 1-based, the column counts characters
+    pub line: usize,
+    pub column: usize,
+    // The offending line, without its line break
+    pub source_line: String,
+    // The contexts we were in, outermost first
+    pub context: Vec<String>,
+    pub message: String,
+}
+
+impl ParseError {
+    pub fn new(input: &str, offset: usize, message: String) -> ParseError {
+        let before = &input[..offset];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let column = before[line_start..].chars().count() + 1;
+        let source_line = input[line_start..].lines().next().unwrap_or("").to_string();
+        ParseError {
+            offset,
+            line,
+            column,
+            source_line,
+            context: Vec::new(),
+            message,
+        }
+    }
+
+    // e.g. in movement: expected forward|up|down
+    pub fn summary(&self) -> String {
+        let mut parts: Vec<String> = self.context.iter().map(|c| format!("in {}", c)).collect();
+        parts.push(self.message.clone());
+        parts.join(": ")
+    }
+
+    // Renders the error like a compiler would, with a caret under the bad character
+    //
+    //     error: in movement: expected forward|up|down
+    //      --> inputs/day2:3:1
+    //       |
+    //     3 | sideways 5
+    //       | ^
+    pub fn render(&self, file: &str) -> String {
+        let number = self.line.to_string();
+        let gutter = " ".repeat(number.len());
+        // Keep tabs so the caret lines up with what the terminal shows
+        let padding: String = self
+            .source_line
+            .chars()
+            .take(self.column - 1)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        format!(
+            "error: {}\n{}--> {}:{}:{}\n{} |\n{} | {}\n{} | {}^",
+            self.summary(),
+            gutter,
+            file,
+            self.line,
+            self.column,
+            gutter,
+            number,
+            self.source_line,
+            gutter,
+            padding
+        )
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Parse(ParseError),
+    // The input parsed, but doesn't make sense for the puzzle
+    Validation(String),
+    // The input makes sense, but there is no answer for it
+    NoSolution(String),
+    NotImplemented,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "{}", e),
+            Error::Parse(e) => write!(f, "parse error at {}:{}: {}", e.line, e.column, e.summary()),
+            Error::Validation(message) => write!(f, "invalid input: {}", message),
+            Error::NoSolution(message) => write!(f, "no solution: {}", message),
+            Error::NotImplemented => write!(f, "not implemented"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+// Describes the innermost error, i.e. what the parser wanted to see
+fn describe(kind: &VerboseErrorKind) -> String {
+    match kind {
+        VerboseErrorKind::Char(c) => format!("expected {:?}", c),
+        // Only `expected` puts a context first
+        VerboseErrorKind::Context(what) => format!("expected {}", what),
+        VerboseErrorKind::Nom(ErrorKind::Digit) => "expected a number".to_string(),
+        VerboseErrorKind::Nom(ErrorKind::Alpha) => "expected a letter".to_string(),
+        VerboseErrorKind::Nom(ErrorKind::Space | ErrorKind::MultiSpace) => {
+            "expected a space".to_string()
+        }
+        VerboseErrorKind::Nom(ErrorKind::Eof) => "expected end of input".to_string(),
+        VerboseErrorKind::Nom(ErrorKind::MapRes) => "expected a valid value".to_string(),
+        VerboseErrorKind::Nom(kind) => {
+            format!("expected {}", kind.description().to_lowercase())
+        }
+    }
+}
+
+// Converts nom's error into ours. `input` must be the whole input, and the
+// error must point somewhere inside it
+pub fn parse_error(input: &str, e: NomErr<VerboseError<&str>>) -> Error {
+    match e {
+        NomErr::Error(e) | NomErr::Failure(e) => match e.errors.split_first() {
+            Some(((remaining, kind), outer)) => {
+                let mut error = ParseError::new(input, input.offset(remaining), describe(kind));
+                // nom lists the contexts from the inside out
+                error.context = outer
+                    .iter()
+                    .rev()
+                    .filter_map(|(_, kind)| match kind {
+                        VerboseErrorKind::Context(context) => Some(context.to_string()),
+                        _ => None,
+                    })
+                    .collect();
+                Error::Parse(error)
             }
-            result
+            None => Error::Parse(ParseError::new(input, 0, "unknown error".to_string())),
+        },
+        NomErr::Incomplete(_) => Error::Parse(ParseError::new(
+            input,
+            input.len(),
+            "unexpected end of input".to_string(),
+        )),
+    }
+}
+
+// Lists stop quietly at the first thing they don't recognize, so anything
+// left over is an error too
+fn finish<T>(input: &str, result: Res<&str, T>) -> Result<T> {
+    match result {
+        Ok((remaining, result)) if remaining.trim().is_empty() => Ok(result),
+        Ok((remaining, _)) => {
+            // Point at the stray character rather than the line break before it
+            let remaining = remaining.trim_start();
+            Err(Error::Parse(ParseError::new(
+                input,
+                input.offset(remaining),
+                format!("unexpected {:?}", remaining.chars().next().unwrap_or(' ')),
+            )))
         }
-        _ => panic!("Unable to read the input..."),
+        Err(e) => Err(parse_error(input, e)),
     }
 }
 
+// Replaces whatever error the parser gives with "expected <what>", for
+// when nom's own description wouldn't help, e.g. a failed alt of tags
+pub fn expected<'a, O>(
+    what: &'static str,
+    mut parser: impl FnMut(&'a str) -> Res<&'a str, O>,
+) -> impl FnMut(&'a str) -> Res<&'a str, O> {
+    move |input: &'a str| {
+        parser(input).map_err(|e| {
+            e.map(|_| VerboseError {
+                errors: vec![(input, VerboseErrorKind::Context(what))],
+            })
+        })
+    }
+}
+
+// Like separated_list1, except that an element that fails after a separator
+// is an error instead of the end of the list, so the error points at it.
+// A separator followed by a blank line still ends the list, which allows
+// trailing line breaks.
+pub fn list<'a, O, S>(
+    mut separator: impl FnMut(&'a str) -> Res<&'a str, S>,
+    mut element: impl FnMut(&'a str) -> Res<&'a str, O>,
+) -> impl FnMut(&'a str) -> Res<&'a str, Vec<O>> {
+    move |input: &'a str| {
+        let (mut input, first) = element(input)?;
+        let mut result = vec![first];
+        loop {
+            let rest = match separator(input) {
+                Ok((rest, _)) => rest,
+                Err(NomErr::Error(_)) => return Ok((input, result)),
+                Err(e) => return Err(e),
+            };
+            if rest.lines().next().unwrap_or("").trim().is_empty() {
+                return Ok((input, result));
+            }
+            let (rest, next) = element(rest)?;
+            result.push(next);
+            input = rest;
+        }
+    }
+}
+
+// Runs a parser over the whole input
+pub fn parse_all<'a, T>(
+    input: &'a str,
+    mut parser: impl FnMut(&'a str) -> Res<&'a str, T>,
+) -> Result<T> {
+    finish(input, parser(input))
+}
+
+// Runs a parser over every line, errors still point into the whole input
+pub fn parse_each_line<'a, T>(
+    input: &'a str,
+    mut parser: impl FnMut(&'a str) -> Res<&'a str, T>,
+) -> Result<Vec<T>> {
+    input
+        .lines()
+        .map(|line| finish(input, parser(line)))
+        .collect()
+}
+
+// Every puzzle has two parts, the second usually unlocked by solving the first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    pub fn number(&self) -> u8 {
+        match self {
+            Part::One => 1,
+            Part::Two => 2,
+        }
+    }
+}
+
+// This could be useful for day 5
+pub fn safe_convert(x: &f64) -> Option<isize> {
+    if x.fract() == 0.0 {
+        Some(*x as isize)
+    } else {
+        None
+    }
+}
+
+// What was passed to panic!, for reporting a panic caught with catch_unwind
+pub fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_default()
+}
+
+// Field-wise instances for structs, see the derive crate for the attributes
+pub use aoc_2021_derive::{Monoid, Semigroup};
+
 pub trait Semigroup {
     fn mappend(_: Self, _: Self) -> Self;
 }
 
-pub trait Monoid {
+pub trait Monoid: Semigroup {
     fn mempty() -> Self;
 }
+
+pub fn mconcat<M: Monoid>(values: impl IntoIterator<Item = M>) -> M {
+    values.into_iter().fold(M::mempty(), M::mappend)
+}
+
+pub fn fold_map<T, M: Monoid>(values: impl IntoIterator<Item = T>, f: impl FnMut(T) -> M) -> M {
+    mconcat(values.into_iter().map(f))
+}
+
+// Below this many values, starting threads costs more than it saves
+pub const PAR_THRESHOLD: usize = 10_000;
+
+// Joins the threads in order, so the partial results are combined in the
+// order of their chunks, and a panic on a thread carries on in this one
+fn join_partials<M: Monoid>(handles: Vec<thread::ScopedJoinHandle<'_, M>>) -> M {
+    mconcat(
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap_or_else(|e| panic::resume_unwind(e))),
+    )
+}
+
+// The same as mconcat, with a chunk of the values folded on each core. Only
+// the grouping changes, so by associativity neither does the result.
+pub fn par_mconcat<M: Monoid + Send>(values: impl IntoIterator<Item = M>) -> M {
+    let mut values: Vec<M> = values.into_iter().collect();
+    if values.len() < PAR_THRESHOLD {
+        return mconcat(values);
+    }
+    let size = values.len().div_ceil(pool::default_workers());
+    // Split off the last chunk until only the first is left
+    let mut chunks: Vec<Vec<M>> = Vec::new();
+    while values.len() > size {
+        let start = (values.len() - 1) / size * size;
+        chunks.push(values.split_off(start));
+    }
+    chunks.push(values);
+    chunks.reverse();
+    thread::scope(|scope| {
+        join_partials(
+            chunks
+                .into_iter()
+                .map(|chunk| scope.spawn(|| mconcat(chunk)))
+                .collect(),
+        )
+    })
+}
+
+// fold_map over a slice, with a chunk of it on each core
+pub fn par_fold_map<T: Sync, M: Monoid + Send>(values: &[T], f: impl Fn(&T) -> M + Sync) -> M {
+    if values.len() < PAR_THRESHOLD {
+        return fold_map(values, f);
+    }
+    let size = values.len().div_ceil(pool::default_workers());
+    let f = &f;
+    thread::scope(|scope| {
+        join_partials(
+            values
+                .chunks(size)
+                .map(|chunk| scope.spawn(move || fold_map(chunk, f)))
+                .collect(),
+        )
+    })
+}
+
+// The identities of + and *, for Sum and Product
+pub trait Zero {
+    fn zero() -> Self;
+}
+
+pub trait One {
+    fn one() -> Self;
+}
+
+macro_rules! number_identities {
+    ($zero:literal, $one:literal: $($number:ty),+) => {
+        $(
+            impl Zero for $number {
+                fn zero() -> Self {
+                    $zero
+                }
+            }
+
+            impl One for $number {
+                fn one() -> Self {
+                    $one
+                }
+            }
+        )+
+    };
+}
+
+number_identities!(0, 1: i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+number_identities!(0.0, 1.0: f32, f64);
+
+// Numbers combine in more than one way, so they only become monoids through
+// one of these
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Sum<T>(pub T);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Product<T>(pub T);
+
+// Min and Max have no mempty in general, wrap them in an Option for one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Min<T>(pub T);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Max<T>(pub T);
+
+// The first or last Some, if there is one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct First<T>(pub Option<T>);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Last<T>(pub Option<T>);
+
+impl<T: Add<Output = T>> Semigroup for Sum<T> {
+    fn mappend(fst: Self, snd: Self) -> Self {
+        Sum(fst.0 + snd.0)
+    }
+}
+
+impl<T: Add<Output = T> + Zero> Monoid for Sum<T> {
+    fn mempty() -> Self {
+        Sum(T::zero())
+    }
+}
+
+impl<T: Mul<Output = T>> Semigroup for Product<T> {
+    fn mappend(fst: Self, snd: Self) -> Self {
+        Product(fst.0 * snd.0)
+    }
+}
+
+impl<T: Mul<Output = T> + One> Monoid for Product<T> {
+    fn mempty() -> Self {
+        Product(T::one())
+    }
+}
+
+// Ties keep the first
+impl<T: Ord> Semigroup for Min<T> {
+    fn mappend(fst: Self, snd: Self) -> Self {
+        if snd.0 < fst.0 {
+            snd
+        } else {
+            fst
+        }
+    }
+}
+
+impl<T: Ord> Semigroup for Max<T> {
+    fn mappend(fst: Self, snd: Self) -> Self {
+        if snd.0 > fst.0 {
+            snd
+        } else {
+            fst
+        }
+    }
+}
+
+impl<T> Semigroup for First<T> {
+    fn mappend(fst: Self, snd: Self) -> Self {
+        First(fst.0.or(snd.0))
+    }
+}
+
+impl<T> Monoid for First<T> {
+    fn mempty() -> Self {
+        First(None)
+    }
+}
+
+impl<T> Semigroup for Last<T> {
+    fn mappend(fst: Self, snd: Self) -> Self {
+        Last(snd.0.or(fst.0))
+    }
+}
+
+impl<T> Monoid for Last<T> {
+    fn mempty() -> Self {
+        Last(None)
+    }
+}
+
+// None is the identity, two Somes combine what's inside
+impl<T: Semigroup> Semigroup for Option<T> {
+    fn mappend(fst: Self, snd: Self) -> Self {
+        match (fst, snd) {
+            (Some(fst), Some(snd)) => Some(T::mappend(fst, snd)),
+            (fst, None) => fst,
+            (None, snd) => snd,
+        }
+    }
+}
+
+impl<T: Semigroup> Monoid for Option<T> {
+    fn mempty() -> Self {
+        None
+    }
+}
+
+impl<T> Semigroup for Vec<T> {
+    fn mappend(mut fst: Self, snd: Self) -> Self {
+        fst.extend(snd);
+        fst
+    }
+}
+
+impl<T> Monoid for Vec<T> {
+    fn mempty() -> Self {
+        Vec::new()
+    }
+}
+
+impl Semigroup for String {
+    fn mappend(mut fst: Self, snd: Self) -> Self {
+        fst.push_str(&snd);
+        fst
+    }
+}
+
+impl Monoid for String {
+    fn mempty() -> Self {
+        String::new()
+    }
+}
+
+// Every key of both, with the values of keys in both combined
+impl<K: Eq + Hash, V: Semigroup> Semigroup for HashMap<K, V> {
+    fn mappend(mut fst: Self, snd: Self) -> Self {
+        for (key, value) in snd {
+            let value = match fst.remove(&key) {
+                Some(first) => V::mappend(first, value),
+                None => value,
+            };
+            fst.insert(key, value);
+        }
+        fst
+    }
+}
+
+impl<K: Eq + Hash, V: Semigroup> Monoid for HashMap<K, V> {
+    fn mempty() -> Self {
+        HashMap::new()
+    }
+}
+
+impl Semigroup for () {
+    fn mappend(_: Self, _: Self) -> Self {}
+}
+
+impl Monoid for () {
+    fn mempty() -> Self {}
+}
+
+// Tuples combine element-wise
+macro_rules! tuple_instances {
+    ($($name:ident $idx:tt),+) => {
+        impl<$($name: Semigroup),+> Semigroup for ($($name,)+) {
+            fn mappend(fst: Self, snd: Self) -> Self {
+                ($($name::mappend(fst.$idx, snd.$idx),)+)
+            }
+        }
+
+        impl<$($name: Monoid),+> Monoid for ($($name,)+) {
+            fn mempty() -> Self {
+                ($($name::mempty(),)+)
+            }
+        }
+    };
+}
+
+tuple_instances!(A 0, B 1);
+tuple_instances!(A 0, B 1, C 2);
+tuple_instances!(A 0, B 1, C 2, D 3);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate::Rng;
+    use crate::laws::{assert_laws, signed};
+    use nom::{
+        bytes::complete::tag,
+        character::complete::{newline, u32},
+        error::context,
+    };
+
+    fn parse_failure<T: fmt::Debug>(result: Result<T>) -> ParseError {
+        match result {
+            Err(Error::Parse(e)) => e,
+            other => panic!("Expected a parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn counts_lines_and_columns_from_one() {
+        let e = ParseError::new("ab\ncdé\nf", 7, "oops".to_string());
+        assert_eq!((e.line, e.column), (2, 4));
+        assert_eq!(e.source_line, "cdé");
+    }
+
+    #[test]
+    fn reports_contexts_outermost_first() {
+        let mut parser = context("outer", context("inner", expected("a or b", tag("a"))));
+        let e = parse_failure(parse_all("c", &mut parser));
+        assert_eq!(e.context, vec!["outer", "inner"]);
+        assert_eq!(e.summary(), "in outer: in inner: expected a or b");
+    }
+
+    #[test]
+    fn renders_a_caret_under_the_bad_character() {
+        let e = parse_failure(parse_all("1,2\n3,x", list(newline, list(tag(","), u32))));
+        assert_eq!(
+            e.render("day6"),
+            "error: expected a number\n --> day6:2:3\n  |\n2 | 3,x\n  |   ^"
+        );
+    }
+
+    #[test]
+    fn lists_fail_at_the_bad_element() {
+        let e = parse_failure(parse_all("1,2,x,4", list(tag(","), u32)));
+        assert_eq!(e.column, 5);
+    }
+
+    #[test]
+    fn lists_allow_trailing_line_breaks() {
+        let mut parser = list(newline, u32);
+        assert_eq!(parse_all("1\n2\n\n", &mut parser).ok(), Some(vec![1, 2]));
+    }
+
+    #[test]
+    fn leftovers_are_errors() {
+        let e = parse_failure(parse_all("12\n\n  x", u32));
+        assert_eq!((e.line, e.column), (3, 3));
+        assert_eq!(e.message, "unexpected 'x'");
+    }
+
+    #[test]
+    fn parse_each_line_points_into_the_whole_input() {
+        let e = parse_failure(parse_each_line("1\n2\nx", u32));
+        assert_eq!((e.offset, e.line, e.column), (4, 3, 1));
+    }
+
+    #[test]
+    fn safe_convert_only_accepts_whole_numbers() {
+        assert_eq!(safe_convert(&1.4), None);
+        assert_eq!(safe_convert(&1.0), Some(1));
+    }
+
+    // Keeps the larger of the two, for #[monoid(with = ...)]
+    mod largest {
+        pub fn mappend(fst: u8, snd: u8) -> u8 {
+            fst.max(snd)
+        }
+
+        pub fn mempty() -> u8 {
+            0
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq, Semigroup, Monoid)]
+    struct Pair(#[monoid(sum)] i32, #[monoid(product)] u64);
+
+    #[derive(Debug, Clone, PartialEq, Semigroup, Monoid)]
+    struct Stats {
+        pair: Pair,
+        #[monoid(with = "largest")]
+        largest: u8,
+        #[monoid(sum)]
+        total: f64,
+    }
+
+    #[test]
+    fn derives_field_wise_instances() {
+        assert_eq!(Pair::mempty(), Pair(0, 1));
+        assert_eq!(Pair::mappend(Pair(2, 3), Pair(-5, 4)), Pair(-3, 12));
+
+        let stats = |pair, largest, total| Stats {
+            pair,
+            largest,
+            total,
+        };
+        assert_eq!(Stats::mempty(), stats(Pair(0, 1), 0, 0.0));
+        assert_eq!(
+            Stats::mappend(stats(Pair(1, 2), 7, 0.5), stats(Pair(1, 5), 3, 1.0)),
+            stats(Pair(2, 10), 7, 1.5)
+        );
+    }
+
+    #[test]
+    fn folds_standard_instances() {
+        assert_eq!(fold_map(1..=4, Sum), Sum(10));
+        assert_eq!(fold_map(1..=4, Product), Product(24));
+        assert_eq!(fold_map([-2i8, 3], Product), Product(-6));
+        assert_eq!(mconcat(Vec::<Sum<f64>>::new()), Sum(0.0));
+        assert_eq!(fold_map([3, 1, 2], |x| Some(Min(x))), Some(Min(1)));
+        assert_eq!(fold_map([3, 1, 2], |x| Some(Max(x))), Some(Max(3)));
+        assert_eq!(mconcat(Vec::<Option<Max<u8>>>::new()), None);
+        assert_eq!(fold_map([None, Some(1), Some(2)], First), First(Some(1)));
+        assert_eq!(fold_map([Some(1), Some(2), None], Last), Last(Some(2)));
+        assert_eq!(mconcat(vec![vec![1], vec![], vec![2, 3]]), vec![1, 2, 3]);
+        assert_eq!(fold_map(["ab", "", "c"], String::from), "abc");
+        assert_eq!(
+            fold_map([1, 2, 3], |x| (Sum(x), Product(x), vec![x])),
+            (Sum(6), Product(6), vec![1, 2, 3])
+        );
+    }
+
+    #[test]
+    fn merges_maps_value_wise() {
+        let tally = fold_map("abca".chars(), |c| HashMap::from([(c, Sum(1))]));
+        assert_eq!(
+            tally,
+            HashMap::from([('a', Sum(2)), ('b', Sum(1)), ('c', Sum(1))])
+        );
+        let lists = HashMap::mappend(
+            HashMap::from([(1, vec!['a']), (2, vec!['b'])]),
+            HashMap::from([(1, vec!['c'])]),
+        );
+        assert_eq!(lists[&1], vec!['a', 'c']);
+        assert_eq!(lists[&2], vec!['b']);
+    }
+
+    fn maybe(rng: &mut Rng, size: usize) -> Option<i64> {
+        if rng.chance(25) {
+            None
+        } else {
+            Some(signed(rng, size))
+        }
+    }
+
+    #[test]
+    fn standard_instances_are_monoids() {
+        assert_laws("Sum", |rng, size| Sum(signed(rng, size)));
+        assert_laws("Product", |rng, size| Product(signed(rng, size)));
+        assert_laws("Option<Min>", |rng, size| maybe(rng, size).map(Min));
+        assert_laws("Option<Max>", |rng, size| maybe(rng, size).map(Max));
+        assert_laws("First", |rng, size| First(maybe(rng, size)));
+        assert_laws("Last", |rng, size| Last(maybe(rng, size)));
+        assert_laws("Vec", |rng, size| {
+            (0..rng.below(size))
+                .map(|_| rng.below(10))
+                .collect::<Vec<_>>()
+        });
+        assert_laws("String", |rng, size| "ab".repeat(rng.below(size)));
+        assert_laws("(Sum, Vec)", |rng, size| {
+            (Sum(signed(rng, size)), vec![rng.below(size)])
+        });
+        assert_laws("HashMap", |rng, size| {
+            fold_map(0..rng.below(size), |_| {
+                HashMap::from([(rng.below(5), Sum(signed(rng, size)))])
+            })
+        });
+    }
+
+    #[test]
+    fn derived_instances_are_monoids() {
+        assert_laws("Stats", |rng, size| Stats {
+            pair: Pair(signed(rng, size) as i32, rng.below(size) as u64),
+            largest: rng.below(256) as u8,
+            // Halves add up exactly
+            total: signed(rng, size) as f64 / 2.0,
+        });
+    }
+
+    #[test]
+    fn folds_in_parallel_in_order() {
+        let numbers: Vec<usize> = (0..PAR_THRESHOLD * 3 + 7).collect();
+        assert_eq!(par_mconcat(numbers.iter().map(|n| vec![*n])), numbers);
+        assert_eq!(
+            par_fold_map(&numbers, |n| Sum(*n)),
+            Sum(numbers.iter().sum())
+        );
+        assert_eq!(
+            par_fold_map(&numbers, |n| Some(Max(*n))),
+            Some(Max(numbers.len() - 1))
+        );
+        // Small inputs are folded where they are
+        assert_eq!(par_mconcat(["a", "b"].map(String::from)), "ab");
+        assert_eq!(par_fold_map(&[] as &[usize], |n| Sum(*n)), Sum(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "too big")]
+    fn passes_on_a_panic_from_a_thread() {
+        let numbers: Vec<usize> = (0..PAR_THRESHOLD * 2).collect();
+        par_fold_map(&numbers, |n| {
+            if *n >= PAR_THRESHOLD {
+                panic!("too big");
+            }
+            Sum(*n)
+        });
+    }
+}
@@ -1,38 +1,15 @@
-// mod day1;
-// mod day2;
-// mod day3;
-// mod day4;
-// mod day5;
-// mod day6;
-// mod day7;
-// mod day8;
-// mod day9;
-mod day10;
+// The command line front end. Everything it runs lives in the library.
+use aoc_2021::{cli, commands};
+use std::env;
+use std::process::ExitCode;
 
-mod lib;
-// mod nalgebra_testing;
-
-// This could be useful for day 5
-fn safe_convert(x: &f64) -> Option<isize> {
-    if x.fract() == 0.0 {
-        Some(*x as isize)
-    } else {
-        None
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match cli::parse(&args) {
+        Ok(command) => commands::run(command),
+        Err(message) => {
+            eprintln!("{}\n\n{}", message, cli::USAGE);
+            ExitCode::from(2)
+        }
     }
 }
-
-fn main() {
-    assert_eq!(safe_convert(&1.4), None);
-    assert_eq!(safe_convert(&1.0), Some(1));
-    // day1::run();
-    // day2::run();
-    // day3::run();
-    // day4::run();
-    // nalgebra_testing::run();
-    // day5::run();
-    // day6::run();
-    // day7::run();
-    // day8::run();
-    // day9::run();
-    day10::run();
-}
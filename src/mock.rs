@@ -0,0 +1,307 @@
+// A stand-in for the puzzle site's answer endpoint, so that `submit` can be
+// tried out, and tested, offline. Answers are checked against the correct ones
+// in an answers ledger, and the reply uses the same sentences the site does.
+//
+// Like the site, a wrong answer locks out any other for a while, and answers
+// given in the meantime are turned away without being checked.
+use super::answers::{Ledger, Verdict};
+use super::lib::Part;
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_PORT: u16 = 8021;
+pub const DEFAULT_COOLDOWN: Duration = Duration::from_secs(60);
+
+// Requests are answered one at a time, so a client that stalls has to be cut
+// off for the others to get a turn
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+// A form with a level and an answer is tiny, anything much bigger is refused
+// before it's read
+const MAX_BODY: usize = 4096;
+
+pub struct Mock {
+    correct: HashMap<(u8, Part), String>,
+    cooldown: Duration,
+    locked_until: Option<Instant>,
+    read_timeout: Duration,
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded: Vec<u8> = Vec::new();
+    let mut idx = 0;
+    while idx < bytes.len() {
+        let hex = value.get(idx + 1..idx + 3);
+        match (
+            bytes[idx],
+            hex.and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+        ) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                idx += 3;
+            }
+            (b'+', _) => {
+                decoded.push(b' ');
+                idx += 1;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                idx += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn form_value<'a>(body: &'a str, key: &str) -> Option<&'a str> {
+    body.split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(k, _)| *k == key)
+        .map(|(_, v)| v)
+}
+
+// e.g. "/2021/day/9/answer" is day 9
+fn parse_path(path: &str) -> Option<u8> {
+    let day = path.strip_prefix("/2021/day/")?.strip_suffix("/answer")?;
+    day.parse().ok()
+}
+
+fn page(text: &str) -> String {
+    format!("<main><article><p>{}</p></article></main>", text)
+}
+
+fn reply(mut stream: TcpStream, status: &str, page: &str) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: text/html\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        page.len(),
+        page
+    )?;
+    stream.flush()
+}
+
+impl Mock {
+    pub fn new(ledger: &Ledger, cooldown: Duration) -> Mock {
+        let correct = ledger
+            .entries
+            .iter()
+            .filter(|e| e.verdict == Verdict::Correct)
+            .map(|e| ((e.day, e.part), e.answer.clone()))
+            .collect();
+        Mock {
+            correct,
+            cooldown,
+            locked_until: None,
+            read_timeout: READ_TIMEOUT,
+        }
+    }
+
+    // The page the site would reply with
+    pub fn respond(&mut self, day: u8, part: Part, answer: &str) -> String {
+        let now = Instant::now();
+        if let Some(left) = self
+            .locked_until
+            .and_then(|until| until.checked_duration_since(now))
+        {
+            let left = left.as_secs() + 1;
+            return page(&format!(
+                "You gave an answer too recently; you have to wait after submitting an \
+                 answer before trying again. You have {}m {}s left to wait.",
+                left / 60,
+                left % 60
+            ));
+        }
+        let Some(correct) = self.correct.get(&(day, part)) else {
+            return page("You don't seem to be solving the right level.");
+        };
+        if answer == correct {
+            return page("That's the right answer! You are one gold star closer.");
+        }
+
+        self.locked_until = Some(now + self.cooldown);
+        let hint = match (answer.parse::<i128>(), correct.parse::<i128>()) {
+            (Ok(answer), Ok(correct)) if answer > correct => "; your answer is too high",
+            (Ok(answer), Ok(correct)) if answer < correct => "; your answer is too low",
+            _ => "",
+        };
+        page(&format!(
+            "That's not the right answer{}. Please wait before trying again.",
+            hint
+        ))
+    }
+
+    fn handle(&mut self, stream: TcpStream) -> io::Result<()> {
+        stream.set_read_timeout(Some(self.read_timeout))?;
+        let mut reader = BufReader::new(stream);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line)?;
+        let mut content_length = 0;
+        loop {
+            let mut header = String::new();
+            if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+                break;
+            }
+            if let Some((name, value)) = header.split_once(':') {
+                if name.eq_ignore_ascii_case("content-length") {
+                    content_length = value.trim().parse().unwrap_or(0);
+                }
+            }
+        }
+        if content_length > MAX_BODY {
+            return reply(
+                reader.into_inner(),
+                "413 Payload Too Large",
+                &page("Too large"),
+            );
+        }
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body)?;
+        let body = String::from_utf8_lossy(&body);
+
+        let fields: Vec<&str> = request_line.split_whitespace().collect();
+        let day = match fields.as_slice() {
+            ["POST", path, _] => parse_path(path),
+            _ => None,
+        };
+        let part = match form_value(&body, "level") {
+            Some("1") => Some(Part::One),
+            Some("2") => Some(Part::Two),
+            _ => None,
+        };
+        let answer = form_value(&body, "answer").map(percent_decode);
+        let (status, page) = match (day, part, answer) {
+            (Some(day), Some(part), Some(answer)) => {
+                eprintln!("Day {}, Part {}: {}", day, part.number(), answer);
+                ("200 OK", self.respond(day, part, &answer))
+            }
+            _ => ("404 Not Found", page("Not found")),
+        };
+
+        reply(reader.into_inner(), status, &page)
+    }
+
+    // Answers one request at a time, until the process is stopped
+    pub fn serve(mut self, listener: TcpListener) -> io::Result<()> {
+        for stream in listener.incoming() {
+            if let Err(e) = stream.and_then(|stream| self.handle(stream)) {
+                eprintln!("Dropped a request: {}", e);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::submit::{self, Endpoint, Outcome};
+    use std::thread;
+
+    fn mock(cooldown: Duration) -> Mock {
+        let ledger = Ledger::parse("9 2 too-low 54756\n9 2 correct 1123524\n").unwrap();
+        Mock::new(&ledger, cooldown)
+    }
+
+    #[test]
+    fn answers_like_the_site() {
+        let mut mock = mock(Duration::ZERO);
+        let outcome =
+            |page: String| submit::parse_response(&format!("HTTP/1.1 200 OK\r\n\r\n{}", page));
+        assert_eq!(
+            outcome(mock.respond(9, Part::Two, "1123524")),
+            Ok(Outcome::Verdict(Verdict::Correct))
+        );
+        assert_eq!(
+            outcome(mock.respond(9, Part::Two, "2000000")),
+            Ok(Outcome::Verdict(Verdict::TooHigh))
+        );
+        assert_eq!(
+            outcome(mock.respond(9, Part::Two, "54756")),
+            Ok(Outcome::Verdict(Verdict::TooLow))
+        );
+        assert_eq!(
+            outcome(mock.respond(9, Part::Two, "abc")),
+            Ok(Outcome::Verdict(Verdict::Wrong))
+        );
+        assert!(outcome(mock.respond(9, Part::One, "526")).is_err());
+    }
+
+    #[test]
+    fn locks_out_answers_after_a_wrong_one() {
+        let mut mock = mock(Duration::from_secs(90));
+        mock.respond(9, Part::Two, "1");
+        let page = mock.respond(9, Part::Two, "1123524");
+        assert!(page.contains("You have 1m 30s left to wait"), "{}", page);
+    }
+
+    #[test]
+    fn submits_over_tcp() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        thread::spawn(move || mock(Duration::from_secs(60)).serve(listener));
+
+        let endpoint = Endpoint::parse(&format!("http://127.0.0.1:{}", port)).unwrap();
+        let submit = |answer: &str| submit::submit(&endpoint, Some("cookie"), 9, Part::Two, answer);
+        assert_eq!(submit("54756"), Ok(Outcome::Verdict(Verdict::TooLow)));
+        assert!(matches!(
+            submit("1123524"),
+            Ok(Outcome::RateLimited { wait: Some(_) })
+        ));
+
+        let wrong_path = Endpoint::parse(&format!("http://127.0.0.1:{}/elsewhere", port)).unwrap();
+        assert!(submit::submit(&wrong_path, None, 9, Part::Two, "1").is_err());
+    }
+
+    #[test]
+    fn cuts_off_a_client_that_sends_nothing() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let mut mock = mock(Duration::ZERO);
+        mock.read_timeout = Duration::from_millis(100);
+        thread::spawn(move || mock.serve(listener));
+
+        let _silent = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        let endpoint = Endpoint::parse(&format!("http://127.0.0.1:{}", port)).unwrap();
+        assert_eq!(
+            submit::submit(&endpoint, None, 9, Part::Two, "1123524"),
+            Ok(Outcome::Verdict(Verdict::Correct))
+        );
+    }
+
+    #[test]
+    fn refuses_a_body_too_large_to_be_an_answer() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        thread::spawn(move || mock(Duration::ZERO).serve(listener));
+
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        write!(
+            stream,
+            "POST /2021/day/9/answer HTTP/1.1\r\nContent-Length: 99999999999\r\n\r\n"
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 413 "), "{}", response);
+
+        // The server is still there for the next one
+        let endpoint = Endpoint::parse(&format!("http://127.0.0.1:{}", port)).unwrap();
+        assert_eq!(
+            submit::submit(&endpoint, None, 9, Part::Two, "1123524"),
+            Ok(Outcome::Verdict(Verdict::Correct))
+        );
+    }
+
+    #[test]
+    fn decodes_form_values() {
+        assert_eq!(percent_decode("a%20b+c%3D"), "a b c=");
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(form_value("level=2&answer=12", "answer"), Some("12"));
+        assert_eq!(parse_path("/2021/day/9/answer"), Some(9));
+        assert_eq!(parse_path("/2021/day/x/answer"), None);
+    }
+}
@@ -0,0 +1,73 @@
+// A small thread pool for running independent jobs, e.g. every day in `all`.
+// Workers take the next job off a shared counter, so a slow job doesn't hold
+// up the ones queued behind it, and results come back in the order of the jobs.
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+// One worker per core, or a single one when that can't be told
+pub fn default_workers() -> usize {
+    thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+pub fn map<T, R, F>(jobs: &[T], workers: usize, f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<R>>> = Mutex::new((0..jobs.len()).map(|_| None).collect());
+    thread::scope(|scope| {
+        for _ in 0..workers.clamp(1, jobs.len().max(1)) {
+            scope.spawn(|| loop {
+                let idx = next.fetch_add(1, Ordering::Relaxed);
+                let Some(job) = jobs.get(idx) else {
+                    break;
+                };
+                let result = f(job);
+                results.lock().unwrap()[idx] = Some(result);
+            });
+        }
+    });
+    // A worker that panicked takes the whole scope down with it, so every
+    // job has a result by now
+    results
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .map(|result| result.expect("Every job has run"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn keeps_the_order_of_the_jobs() {
+        let jobs: Vec<u64> = (0..20).collect();
+        // Earlier jobs take longer, so they finish last
+        let results = map(&jobs, 4, |n| {
+            thread::sleep(Duration::from_millis(20 - n));
+            n * n
+        });
+        assert_eq!(results, jobs.iter().map(|n| n * n).collect::<Vec<u64>>());
+    }
+
+    #[test]
+    fn spreads_the_jobs_over_the_workers() {
+        let jobs: Vec<usize> = (0..8).collect();
+        let threads = map(&jobs, 4, |_| {
+            thread::sleep(Duration::from_millis(20));
+            thread::current().id()
+        });
+        let mut distinct = threads.clone();
+        distinct.sort_by_key(|id| format!("{:?}", id));
+        distinct.dedup();
+        assert!(distinct.len() > 1 && distinct.len() <= 4);
+        assert!(map(&Vec::<u8>::new(), 4, |n| *n).is_empty());
+        assert_eq!(map(&[1, 2, 3], 0, |n| n + 1), vec![2, 3, 4]);
+    }
+}
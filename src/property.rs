@@ -0,0 +1,236 @@
+// Property tests: check that something holds for many random cases, and when
+// it doesn't, shrink the failing case to a minimal counterexample.
+//
+// A property is a generator, a shrinker and the check itself:
+//
+//     assert_property(
+//         "reversing twice gives the same list",
+//         |rng, size| (0..size).map(|_| rng.below(10)).collect::<Vec<usize>>(),
+//         |v| shrink_vec(v),
+//         |v| { ... Ok(()) or Err(why) },
+//     );
+//
+// The shrinker lists simpler versions of a case, and shrinking keeps taking the
+// first of them that still fails until none does. Cases get bigger as the run
+// goes on, so the small ones are tried first.
+use super::generate::Rng;
+use super::lib::panic_message;
+use std::fmt::Debug;
+use std::panic::{self, AssertUnwindSafe};
+
+pub struct Config {
+    pub cases: usize,
+    pub max_size: usize,
+    pub seed: u64,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            cases: 100,
+            max_size: 50,
+            seed: 2021,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Failure<T> {
+    pub case: usize,
+    pub original: T,
+    pub minimal: T,
+    pub shrinks: usize,
+    pub message: String,
+}
+
+// Stops a shrinker that never runs out of candidates
+const MAX_SHRINKS: usize = 1000;
+
+// A panic fails the property like an Err does
+fn holds<T>(property: &impl Fn(&T) -> Result<(), String>, case: &T) -> Result<(), String> {
+    panic::catch_unwind(AssertUnwindSafe(|| property(case)))
+        .unwrap_or_else(|payload| Err(format!("panicked: {}", panic_message(payload.as_ref()))))
+}
+
+pub fn check<T: Clone>(
+    config: &Config,
+    generate: impl Fn(&mut Rng, usize) -> T,
+    shrink: impl Fn(&T) -> Vec<T>,
+    property: impl Fn(&T) -> Result<(), String>,
+) -> Result<(), Failure<T>> {
+    let mut rng = Rng::new(config.seed);
+    for case in 0..config.cases {
+        let size = 1 + case * config.max_size / config.cases.max(1);
+        let original = generate(&mut rng, size);
+        let mut message = match holds(&property, &original) {
+            Ok(()) => continue,
+            Err(message) => message,
+        };
+
+        let mut minimal = original.clone();
+        let mut shrinks = 0;
+        while shrinks < MAX_SHRINKS {
+            let smaller = shrink(&minimal)
+                .into_iter()
+                .find_map(|candidate| Some((holds(&property, &candidate).err()?, candidate)));
+            match smaller {
+                Some((why, candidate)) => {
+                    message = why;
+                    minimal = candidate;
+                    shrinks += 1;
+                }
+                None => break,
+            }
+        }
+        return Err(Failure {
+            case,
+            original,
+            minimal,
+            shrinks,
+            message,
+        });
+    }
+    Ok(())
+}
+
+// Panics with the minimal counterexample when the property doesn't hold
+pub fn assert_property<T: Clone + Debug>(
+    name: &str,
+    generate: impl Fn(&mut Rng, usize) -> T,
+    shrink: impl Fn(&T) -> Vec<T>,
+    property: impl Fn(&T) -> Result<(), String>,
+) {
+    let config = Config::default();
+    if let Err(failure) = check(&config, generate, shrink, property) {
+        panic!(
+            "Property failed: {}\n\
+             case {} with seed {}, shrunk {} times to:\n{:#?}\n{}",
+            name, failure.case, config.seed, failure.shrinks, failure.minimal, failure.message
+        );
+    }
+}
+
+// Shorter lists: without a run of items, from half the list down to one item
+pub fn shrink_vec<T: Clone>(items: &[T]) -> Vec<Vec<T>> {
+    let mut candidates: Vec<Vec<T>> = Vec::new();
+    let mut chunk = items.len() / 2;
+    while chunk > 0 {
+        for start in (0..items.len()).step_by(chunk) {
+            let end = (start + chunk).min(items.len());
+            candidates.push([&items[..start], &items[end..]].concat());
+        }
+        chunk /= 2;
+    }
+    if items.len() == 1 {
+        candidates.push(Vec::new());
+    }
+    candidates
+}
+
+// The same list with one item shrunk
+pub fn shrink_each<T: Clone>(items: &[T], shrink: impl Fn(&T) -> Vec<T>) -> Vec<Vec<T>> {
+    let mut candidates: Vec<Vec<T>> = Vec::new();
+    for (idx, item) in items.iter().enumerate() {
+        for smaller in shrink(item) {
+            let mut candidate = items.to_vec();
+            candidate[idx] = smaller;
+            candidates.push(candidate);
+        }
+    }
+    candidates
+}
+
+pub fn shrink_usize(n: &usize) -> Vec<usize> {
+    let mut candidates: Vec<usize> = vec![0, n / 2, n.saturating_sub(1)];
+    candidates.dedup();
+    candidates.retain(|candidate| candidate < n);
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn numbers(rng: &mut Rng, size: usize) -> Vec<usize> {
+        (0..size).map(|_| rng.below(100)).collect()
+    }
+
+    fn shrink_numbers(items: &[usize]) -> Vec<Vec<usize>> {
+        let mut candidates = shrink_vec(items);
+        candidates.extend(shrink_each(items, shrink_usize));
+        candidates
+    }
+
+    #[test]
+    fn a_property_that_holds_passes() {
+        let result = check(
+            &Config::default(),
+            numbers,
+            |v| shrink_numbers(v),
+            |v| {
+                if v.iter().all(|n| *n < 100) {
+                    Ok(())
+                } else {
+                    Err("too big".to_string())
+                }
+            },
+        );
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn shrinks_to_a_minimal_counterexample() {
+        let failure = check(
+            &Config::default(),
+            numbers,
+            |v| shrink_numbers(v),
+            |v| {
+                if v.iter().sum::<usize>() < 150 {
+                    Ok(())
+                } else {
+                    Err(format!("sum is {}", v.iter().sum::<usize>()))
+                }
+            },
+        )
+        .unwrap_err();
+        assert!(failure.original.iter().sum::<usize>() >= 150);
+        // Any smaller list, or smaller item, would sum to less than 150
+        assert_eq!(failure.minimal.iter().sum::<usize>(), 150);
+        assert_eq!(failure.message, "sum is 150");
+        assert!(failure.shrinks > 0);
+    }
+
+    #[test]
+    fn a_panic_is_a_failure() {
+        let failure = check(
+            &Config::default(),
+            numbers,
+            |v| shrink_numbers(v),
+            |v| {
+                let _ = v[2];
+                Ok(())
+            },
+        )
+        .unwrap_err();
+        assert_eq!(failure.minimal, Vec::<usize>::new());
+        assert!(failure.message.starts_with("panicked: index out of bounds"));
+    }
+
+    #[test]
+    fn shrinks_lists_by_halves_then_single_items() {
+        assert_eq!(
+            shrink_vec(&[1, 2, 3, 4]),
+            vec![
+                vec![3, 4],
+                vec![1, 2],
+                vec![2, 3, 4],
+                vec![1, 3, 4],
+                vec![1, 2, 4],
+                vec![1, 2, 3]
+            ]
+        );
+        assert_eq!(shrink_vec(&[1]), vec![Vec::<i32>::new()]);
+        assert_eq!(shrink_usize(&10), vec![0, 5, 9]);
+        assert!(shrink_usize(&0).is_empty());
+    }
+}
@@ -0,0 +1,312 @@
+use super::bench::{Stats, Step};
+use super::diff::{Comparison, Outcome};
+use super::input::Source;
+use super::lib::{Error, Part, Result};
+use super::solution::{Answer, PartRun, Run};
+use super::trace::Event;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Text,
+    // One JSON object per line, for each day and part
+    Json,
+}
+
+impl Format {
+    pub fn parse(value: &str) -> Option<Format> {
+        match value {
+            "text" => Some(Format::Text),
+            "json" => Some(Format::Json),
+            _ => None,
+        }
+    }
+}
+
+// Pick a unit so the slow days stand out without counting digits
+pub fn format_duration(duration: &Duration) -> String {
+    let nanos = duration.as_nanos();
+    if nanos < 1_000 {
+        format!("{}ns", nanos)
+    } else if nanos < 1_000_000 {
+        format!("{:.2}µs", nanos as f64 / 1e3)
+    } else if nanos < 1_000_000_000 {
+        format!("{:.2}ms", nanos as f64 / 1e6)
+    } else {
+        format!("{:.2}s", nanos as f64 / 1e9)
+    }
+}
+
+// Returns the answer and solve time columns
+fn format_part(part_run: &PartRun) -> (String, String) {
+    match &part_run.answer {
+        Ok(answer) => (answer.to_string(), format_duration(&part_run.elapsed)),
+        Err(Error::NotImplemented) => ("not implemented".to_string(), "-".to_string()),
+        Err(e) => (format!("error: {}", e), format_duration(&part_run.elapsed)),
+    }
+}
+
+// The days run at the same time, so the time they took adds up to more than
+// the wall-clock time
+pub fn print_table(runs: &[(u8, Run)], wall: Duration, workers: usize) {
+    println!(
+        "{:>3}  {:>4}  {:<16}  {:>10}  {:>10}",
+        "Day", "Part", "Answer", "Parse", "Solve"
+    );
+    // The parse time is shared by both parts, so it is only counted once per day
+    for (day, run) in runs {
+        for part_run in &run.parts {
+            let (answer, solve) = format_part(part_run);
+            println!(
+                "{:>3}  {:>4}  {:<16}  {:>10}  {:>10}",
+                day,
+                part_run.part.number(),
+                answer,
+                format_duration(&run.parse),
+                solve,
+            );
+        }
+    }
+
+    let parse: Duration = runs.iter().map(|(_, run)| run.parse).sum();
+    let solve: Duration = runs.iter().map(|(_, run)| run.solve()).sum();
+    println!(
+        "{:<27}  {:>10}  {:>10}",
+        "Total",
+        format_duration(&parse),
+        format_duration(&solve)
+    );
+    // Each day's time is measured on its own worker, so the sum can be more
+    // than the wall-clock time
+    let total: Duration = runs.iter().map(|(_, run)| run.total()).sum();
+    println!(
+        "The days took {} summed, {} wall-clock on {} worker{}",
+        format_duration(&total),
+        format_duration(&wall),
+        workers,
+        if workers == 1 { "" } else { "s" }
+    );
+}
+
+// One row per step, with the previous bench of that step if there was one
+pub fn print_bench(rows: &[(u8, Step, Stats, Option<Stats>)], threshold: f64) {
+    println!(
+        "{:>3}  {:<5}  {:>10}  {:>10}  {:>10}  {:>8}",
+        "Day", "Step", "Min", "Median", "p95", "Change"
+    );
+    for (day, step, stats, previous) in rows {
+        let change = match previous {
+            Some(previous) => {
+                let change = stats.change(previous);
+                let flag = if change > threshold {
+                    "  regression"
+                } else {
+                    ""
+                };
+                format!("{:>+7.1}%{}", change, flag)
+            }
+            None => format!("{:>8}", "-"),
+        };
+        println!(
+            "{:>3}  {:<5}  {:>10}  {:>10}  {:>10}  {}",
+            day,
+            step.to_string(),
+            format_duration(&stats.min),
+            format_duration(&stats.median),
+            format_duration(&stats.p95),
+            change
+        );
+    }
+}
+
+fn json_string(value: &str) -> String {
+    let mut result = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\t' => result.push_str("\\t"),
+            c if (c as u32) < 0x20 => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+fn json_nanos(duration: Option<Duration>) -> String {
+    match duration {
+        Some(duration) => duration.as_nanos().to_string(),
+        None => "null".to_string(),
+    }
+}
+
+// Numbers stay numbers, so scripts don't have to convert them back
+//
+//     {"day":2,"part":1,"status":"ok","answer":2272262,"error":null,
+//      "parse_ns":81234,"elapsed_ns":1520,"input":"inputs/day2"}
+//
+// status is ok, not_implemented or error. When parsing fails every part gets
+// the parse error and no timings.
+pub fn json_record(
+    day: u8,
+    part: Part,
+    answer: std::result::Result<&Answer, &Error>,
+    parse: Option<Duration>,
+    elapsed: Option<Duration>,
+    source: &Source,
+) -> String {
+    let null = || "null".to_string();
+    let (status, answer, error) = match answer {
+        Ok(Answer::Number(n)) => ("ok", n.to_string(), null()),
+        Ok(Answer::Text(s)) => ("ok", json_string(s), null()),
+        Err(Error::NotImplemented) => ("not_implemented", null(), null()),
+        Err(e) => ("error", null(), json_string(&e.to_string())),
+    };
+    format!(
+        "{{\"day\":{},\"part\":{},\"status\":\"{}\",\"answer\":{},\"error\":{},\"parse_ns\":{},\"elapsed_ns\":{},\"input\":{}}}",
+        day,
+        part.number(),
+        status,
+        answer,
+        error,
+        json_nanos(parse),
+        json_nanos(elapsed),
+        json_string(&source.to_string())
+    )
+}
+
+pub fn print_json(day: u8, parts: &[Part], run: &Result<Run>, source: &Source) {
+    match run {
+        Ok(run) => {
+            for part_run in &run.parts {
+                let record = json_record(
+                    day,
+                    part_run.part,
+                    part_run.answer.as_ref(),
+                    Some(run.parse),
+                    Some(part_run.elapsed),
+                    source,
+                );
+                println!("{}", record);
+            }
+        }
+        Err(e) => {
+            for part in parts {
+                println!("{}", json_record(day, *part, Err(e), None, None, source));
+            }
+        }
+    }
+}
+
+fn print_outcomes(outcomes: &[Outcome]) {
+    let width = outcomes
+        .iter()
+        .map(|(name, _)| name.len())
+        .max()
+        .unwrap_or(0);
+    for (name, outcome) in outcomes {
+        match outcome {
+            Ok(answer) => println!("  {:<width$}  {}", name, answer, width = width),
+            Err(e) => println!("  {:<width$}  {}", name, e, width = width),
+        }
+    }
+}
+
+// One input's answers, as watch prints them after each change
+pub fn print_run(source: &Source, run: &Run) {
+    println!("{} (parsed in {})", source, format_duration(&run.parse));
+    for part_run in &run.parts {
+        let (answer, solve) = format_part(part_run);
+        println!(
+            "  Part {}: {:<16}  {:>10}",
+            part_run.part.number(),
+            answer,
+            solve
+        );
+    }
+}
+
+// What a part did on the way to its answer, as run --explain prints it
+pub fn print_events(events: &[Event]) {
+    if events.is_empty() {
+        println!("  (nothing to explain)");
+    }
+    for event in events {
+        println!("  {}", event.message);
+    }
+}
+
+// Short prefixes are printed in full so they can be pasted into a test
+pub fn print_comparison(day: u8, comparison: &Comparison) {
+    println!("Day {}, Part {}", day, comparison.part.number());
+    print_outcomes(&comparison.outcomes);
+    if comparison.outcomes.len() < 2 {
+        println!("Only one implementation, nothing to compare");
+    }
+    if let Some(disagreement) = &comparison.disagreement {
+        println!(
+            "The implementations disagree on the first {} line(s):",
+            disagreement.lines
+        );
+        if disagreement.lines <= 20 {
+            for line in disagreement.prefix.lines() {
+                println!("  | {}", line);
+            }
+        }
+        print_outcomes(&disagreement.outcomes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn picks_a_readable_unit() {
+        assert_eq!(format_duration(&Duration::from_nanos(999)), "999ns");
+        assert_eq!(format_duration(&Duration::from_nanos(1_500)), "1.50µs");
+        assert_eq!(format_duration(&Duration::from_millis(12)), "12.00ms");
+        assert_eq!(format_duration(&Duration::from_secs(2)), "2.00s");
+    }
+
+    #[test]
+    fn escapes_json_strings() {
+        assert_eq!(
+            json_string("expected \" -> \"\n\t\\\u{1}"),
+            "\"expected \\\" -> \\\"\\n\\t\\\\\\u0001\""
+        );
+    }
+
+    #[test]
+    fn writes_one_record_per_part() {
+        let source = Source::File(PathBuf::from("inputs/day2"));
+        let answer = Answer::Number(150);
+        assert_eq!(
+            json_record(
+                2,
+                Part::One,
+                Ok(&answer),
+                Some(Duration::from_nanos(10)),
+                Some(Duration::from_nanos(5)),
+                &source
+            ),
+            "{\"day\":2,\"part\":1,\"status\":\"ok\",\"answer\":150,\"error\":null,\
+             \"parse_ns\":10,\"elapsed_ns\":5,\"input\":\"inputs/day2\"}"
+        );
+        assert_eq!(
+            json_record(
+                6,
+                Part::One,
+                Err(&Error::NotImplemented),
+                None,
+                None,
+                &Source::Stdin
+            ),
+            "{\"day\":6,\"part\":1,\"status\":\"not_implemented\",\"answer\":null,\"error\":null,\
+             \"parse_ns\":null,\"elapsed_ns\":null,\"input\":\"<stdin>\"}"
+        );
+    }
+}
@@ -0,0 +1,285 @@
+// Sets up a new day with the same layout as the others:
+//
+//     src/day<N>.rs        a Solution with a nom parser, an input generator and a test stub
+//     inputs/day<N>        empty, for the puzzle input
+//     inputs/day<N>.test   empty, for the example from the puzzle description
+//
+// and registers it by editing aoc.rs, solution.rs and tests/samples.rs, keeping
+// the order cargo fmt would use.
+use super::input;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// The stub for a new day, with Day0 and day0 renamed by `template`. Both parts
+// start out unimplemented, to match the empty row in tests/samples.rs. It's
+// built and tested as day0 too, against the empty inputs/day0.test.
+const TEMPLATE: &str = include_str!("template.rs");
+
+pub fn template(day: u8) -> String {
+    TEMPLATE
+        .replace("Day0", &format!("Day{}", day))
+        .replace("day0", &module(day))
+}
+
+fn module(day: u8) -> String {
+    format!("day{}", day)
+}
+
+// cargo fmt sorts modules and imports as strings, so day10 comes before day2
+fn insert_sorted(names: &mut Vec<String>, name: String) -> Result<(), String> {
+    if names.contains(&name) {
+        return Err(format!("{} is already registered", name));
+    }
+    names.push(name);
+    names.sort();
+    Ok(())
+}
+
+// Adds `pub mod day<N>;` to the block of day modules in aoc.rs
+pub fn add_module(aoc_rs: &str, day: u8) -> Result<String, String> {
+    let lines: Vec<&str> = aoc_rs.lines().collect();
+    let is_day = |line: &str| line.starts_with("pub mod day") && line.ends_with(';');
+    let start = lines
+        .iter()
+        .position(|line| is_day(line))
+        .ok_or("Unable to find the day modules in aoc.rs")?;
+    let end = start
+        + lines[start..]
+            .iter()
+            .take_while(|line| is_day(line))
+            .count();
+
+    let mut names: Vec<String> = lines[start..end]
+        .iter()
+        .map(|line| line["pub mod ".len()..line.len() - 1].to_string())
+        .collect();
+    insert_sorted(&mut names, module(day))?;
+
+    let mut result: Vec<String> = lines[..start].iter().map(|l| l.to_string()).collect();
+    result.extend(names.iter().map(|name| format!("pub mod {};", name)));
+    result.extend(lines[end..].iter().map(|l| l.to_string()));
+    Ok(result.join("\n") + "\n")
+}
+
+// Lays out `use super::{...};` the way cargo fmt does, on one line if it fits
+fn format_use(names: &[String]) -> String {
+    let single = format!("use super::{{{}}};", names.join(", "));
+    if single.len() <= 100 {
+        return single;
+    }
+    let mut lines: Vec<String> = Vec::new();
+    let mut line = String::from("   ");
+    for name in names {
+        if line.len() + name.len() + 2 > 100 {
+            lines.push(line);
+            line = String::from("   ");
+        }
+        line.push(' ');
+        line.push_str(name);
+        line.push(',');
+    }
+    lines.push(line);
+    format!("use super::{{\n{}\n}};", lines.join("\n"))
+}
+
+// Imports the new day in solution.rs and adds it to the registry
+pub fn add_to_registry(solution_rs: &str, day: u8) -> Result<String, String> {
+    let start = solution_rs
+        .find("use super::{")
+        .ok_or("Unable to find the day imports in solution.rs")?;
+    let end = start
+        + solution_rs[start..]
+            .find("};")
+            .ok_or("Unable to find the day imports in solution.rs")?
+        + "};".len();
+    let mut names: Vec<String> = solution_rs[start + "use super::{".len()..end - "};".len()]
+        .split(',')
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .collect();
+    insert_sorted(&mut names, module(day))?;
+    let result = format!(
+        "{}{}{}",
+        &solution_rs[..start],
+        format_use(&names),
+        &solution_rs[end..]
+    );
+
+    let entry = format!(
+        "    Day {{\n        number: {},\n        solution: &day{}::Day{},\n    }},\n",
+        day, day, day
+    );
+    let registry = result
+        .find("pub static REGISTRY")
+        .ok_or("Unable to find the registry in solution.rs")?;
+    let close = registry
+        + result[registry..]
+            .find("];")
+            .ok_or("Unable to find the end of the registry in solution.rs")?;
+    // Registry entries stay in day order
+    let mut insert_at = close;
+    for (offset, _) in result[registry..close].match_indices("    Day {\n") {
+        let at = registry + offset;
+        let number = result[at..]
+            .split("number: ")
+            .nth(1)
+            .and_then(|rest| rest.split(',').next())
+            .and_then(|number| number.parse::<u8>().ok());
+        if number.is_some_and(|number| number > day) {
+            insert_at = at;
+            break;
+        }
+    }
+    Ok(format!(
+        "{}{}{}",
+        &result[..insert_at],
+        entry,
+        &result[insert_at..]
+    ))
+}
+
+// The new day has no example answers yet, so both parts start as not implemented
+pub fn add_expected(samples_rs: &str, day: u8) -> Result<String, String> {
+    let table = samples_rs
+        .find("const EXPECTED")
+        .ok_or("Unable to find the expected answers in tests/samples.rs")?;
+    let close = table
+        + samples_rs[table..]
+            .find("];")
+            .ok_or("Unable to find the end of the expected answers in tests/samples.rs")?;
+    let mut insert_at = close;
+    for (offset, _) in samples_rs[table..close].match_indices("    (") {
+        let at = table + offset;
+        let number = samples_rs[at + "    (".len()..]
+            .split(',')
+            .next()
+            .and_then(|number| number.parse::<u8>().ok());
+        match number {
+            Some(number) if number == day => {
+                return Err(format!("Day {} already has expected answers", day))
+            }
+            Some(number) if number > day => {
+                insert_at = at;
+                break;
+            }
+            _ => (),
+        }
+    }
+    Ok(format!(
+        "{}    ({}, None, None),\n{}",
+        &samples_rs[..insert_at],
+        day,
+        &samples_rs[insert_at..]
+    ))
+}
+
+fn read(path: &Path) -> Result<String, String> {
+    fs::read_to_string(path).map_err(|e| format!("Unable to read {}: {}", path.display(), e))
+}
+
+fn write(path: &Path, contents: &str) -> Result<(), String> {
+    fs::write(path, contents).map_err(|e| format!("Unable to write {}: {}", path.display(), e))
+}
+
+// Returns the files that were created or changed. Everything is checked before
+// anything is written, so a failure leaves the tree alone.
+pub fn new_day(root: &Path, day: u8) -> Result<Vec<PathBuf>, String> {
+    let source = root.join("src").join(format!("day{}.rs", day));
+    if source.exists() {
+        return Err(format!("{} already exists", source.display()));
+    }
+    let aoc_rs = root.join("src").join("aoc.rs");
+    let solution_rs = root.join("src").join("solution.rs");
+    let samples_rs = root.join("tests").join("samples.rs");
+    let aoc = add_module(&read(&aoc_rs)?, day)?;
+    let solution = add_to_registry(&read(&solution_rs)?, day)?;
+    let samples = add_expected(&read(&samples_rs)?, day)?;
+
+    write(&source, &template(day))?;
+    write(&aoc_rs, &aoc)?;
+    write(&solution_rs, &solution)?;
+    write(&samples_rs, &samples)?;
+    let mut changed = vec![source, aoc_rs, solution_rs, samples_rs];
+
+    // Keep any input that was already downloaded
+    let inputs = root.join(input::DEFAULT_DIR);
+    for path in [
+        input::input_path(&inputs, day),
+        input::sample_path(&inputs, day),
+    ] {
+        if !path.exists() {
+            write(&path, "")?;
+            changed.push(path);
+        }
+    }
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_the_stub_after_the_day() {
+        let source = template(11);
+        assert!(source.contains("pub struct Day11;"));
+        assert!(source.contains("include_str!(\"../inputs/day11.test\")"));
+        assert!(!source.contains("day0") && !source.contains("Day0"));
+    }
+
+    #[test]
+    fn keeps_modules_in_string_order() {
+        let aoc_rs = "// header\n\npub mod day1;\npub mod day10;\npub mod day2;\n\npub mod cli;\n";
+        assert_eq!(
+            add_module(aoc_rs, 11).unwrap(),
+            "// header\n\npub mod day1;\npub mod day10;\npub mod day11;\npub mod day2;\n\npub mod cli;\n"
+        );
+        assert!(add_module(aoc_rs, 2).is_err());
+    }
+
+    #[test]
+    fn registers_the_day_in_order() {
+        let solution_rs = "use super::{day1, day3};
+
+pub static REGISTRY: &[Day] = &[
+    Day {
+        number: 1,
+        solution: &day1::Day1,
+    },
+    Day {
+        number: 3,
+        solution: &day3::Day3,
+    },
+];
+";
+        let result = add_to_registry(solution_rs, 2).unwrap();
+        assert!(result.starts_with("use super::{day1, day2, day3};"));
+        let one = result.find("number: 1,").unwrap();
+        let two = result.find("number: 2,").unwrap();
+        let three = result.find("number: 3,").unwrap();
+        assert!(one < two && two < three);
+        assert!(result.contains("solution: &day2::Day2,"));
+        assert!(add_to_registry(&result, 2).is_err());
+    }
+
+    #[test]
+    fn wraps_long_imports_like_cargo_fmt() {
+        let names: Vec<String> = (1..=25).map(module).collect();
+        let formatted = format_use(&names);
+        assert!(formatted.starts_with("use super::{\n    day1, day2,"));
+        assert!(formatted.lines().all(|line| line.len() <= 100));
+        assert!(formatted.ends_with("day25,\n};"));
+    }
+
+    #[test]
+    fn adds_an_empty_expected_row() {
+        let samples_rs = "const EXPECTED: &[(u8, Option<&str>, Option<&str>)] = &[
+    (1, Some(\"7\"), Some(\"5\")),
+    (10, Some(\"26397\"), Some(\"288957\")),
+];
+";
+        let result = add_expected(samples_rs, 2).unwrap();
+        assert!(result.contains("(1, Some(\"7\"), Some(\"5\")),\n    (2, None, None),\n    (10,"));
+        assert!(add_expected(&result, 10).is_err());
+    }
+}
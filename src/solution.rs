@@ -0,0 +1,269 @@
+use super::generate::Rng;
+use super::image::Picture;
+use super::lib::{panic_message, Error, Part, Result};
+use super::trace::{self, Event};
+use super::{day1, day10, day2, day3, day4, day5, day6, day7, day8, day9};
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::time::{Duration, Instant};
+
+// Almost every answer is a number, but some puzzles want a word instead. Numbers
+// are wide enough for any u64 or i64 a solver comes up with.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Answer {
+    Number(i128),
+    Text(String),
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Number(n) => write!(f, "{}", n),
+            Answer::Text(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+// Every integer type an answer comes in fits in an i128, so none of these
+// can lose anything
+impl From<i64> for Answer {
+    fn from(n: i64) -> Self {
+        Answer::Number(n.into())
+    }
+}
+
+impl From<u32> for Answer {
+    fn from(n: u32) -> Self {
+        Answer::Number(n.into())
+    }
+}
+
+impl From<usize> for Answer {
+    fn from(n: usize) -> Self {
+        Answer::Number(n as i128)
+    }
+}
+
+impl From<String> for Answer {
+    fn from(s: String) -> Self {
+        Answer::Text(s)
+    }
+}
+
+// Another way of solving a part, which `diff` checks against the Solution's own
+pub struct Alternative<I> {
+    pub name: &'static str,
+    pub part: Part,
+    pub solve: fn(&I) -> Result<Answer>,
+}
+
+// Parse once, then solve each part from the parsed input
+//
+// A part that isn't overridden hasn't been implemented (yet)
+pub trait Solution {
+    type Input;
+
+    fn parse(&self, input: &str) -> Result<Self::Input>;
+
+    fn part1(&self, _input: &Self::Input) -> Result<Answer> {
+        Err(Error::NotImplemented)
+    }
+
+    fn part2(&self, _input: &Self::Input) -> Result<Answer> {
+        Err(Error::NotImplemented)
+    }
+
+    fn alternatives(&self) -> Vec<Alternative<Self::Input>> {
+        Vec::new()
+    }
+
+    // A random, well-formed input of roughly `size` lines that parse accepts
+    fn generate(&self, _rng: &mut Rng, _size: usize) -> Option<String> {
+        None
+    }
+
+    // Grids worth looking at as images, e.g. a map and what was found on it
+    fn pictures(&self, _input: &Self::Input) -> Vec<Picture> {
+        Vec::new()
+    }
+}
+
+// The name diff uses for the Solution's own part1 and part2
+pub const MAIN_IMPLEMENTATION: &str = "solution";
+
+pub struct PartRun {
+    pub part: Part,
+    pub answer: Result<Answer>,
+    pub elapsed: Duration,
+    // Only recorded when the run is explained
+    pub events: Vec<Event>,
+}
+
+pub struct Run {
+    pub parse: Duration,
+    pub parts: Vec<PartRun>,
+}
+
+impl Run {
+    pub fn solve(&self) -> Duration {
+        self.parts.iter().map(|p| p.elapsed).sum()
+    }
+
+    pub fn total(&self) -> Duration {
+        self.parse + self.solve()
+    }
+
+    // Unimplemented parts are expected, any other error isn't
+    pub fn failed(&self) -> bool {
+        self.parts
+            .iter()
+            .any(|p| matches!(p.answer, Err(ref e) if !matches!(e, Error::NotImplemented)))
+    }
+}
+
+// An alternative that panics, e.g. by indexing past the end of a short input,
+// still has to show up in the comparison
+fn catch_panic(solve: impl FnOnce() -> Result<Answer>) -> Result<Answer> {
+    panic::catch_unwind(AssertUnwindSafe(solve)).unwrap_or_else(|payload| {
+        Err(Error::NoSolution(format!(
+            "panicked: {}",
+            panic_message(payload.as_ref())
+        )))
+    })
+}
+
+fn run_parts<S: Solution>(solution: &S, input: &str, parts: &[Part], explain: bool) -> Result<Run> {
+    let start = Instant::now();
+    let parsed = solution.parse(input)?;
+    let parse = start.elapsed();
+
+    let parts = parts
+        .iter()
+        .map(|part| {
+            let solve = || match part {
+                Part::One => solution.part1(&parsed),
+                Part::Two => solution.part2(&parsed),
+            };
+            let start = Instant::now();
+            let (answer, events) = if explain {
+                trace::record(solve)
+            } else {
+                (solve(), Vec::new())
+            };
+            PartRun {
+                part: *part,
+                answer,
+                elapsed: start.elapsed(),
+                events,
+            }
+        })
+        .collect();
+    Ok(Run { parse, parts })
+}
+
+// Solution has an associated type, so the registry can't hold it directly.
+// Runner hides the input type behind a single call.
+pub trait Runner: Sync {
+    fn run(&self, input: &str, parts: &[Part]) -> Result<Run>;
+
+    // Like run, but keeps the trace events each part emits
+    fn explain(&self, input: &str, parts: &[Part]) -> Result<Run>;
+
+    // Solves one part with every implementation, the Solution's own first
+    fn solve_each(&self, input: &str, part: Part) -> Result<Vec<(&'static str, Result<Answer>)>>;
+
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String>;
+
+    fn pictures(&self, input: &str) -> Result<Vec<Picture>>;
+}
+
+impl<S> Runner for S
+where
+    S: Solution + Sync,
+{
+    fn run(&self, input: &str, parts: &[Part]) -> Result<Run> {
+        run_parts(self, input, parts, false)
+    }
+
+    fn explain(&self, input: &str, parts: &[Part]) -> Result<Run> {
+        run_parts(self, input, parts, true)
+    }
+
+    fn solve_each(&self, input: &str, part: Part) -> Result<Vec<(&'static str, Result<Answer>)>> {
+        let parsed = self.parse(input)?;
+        let main = catch_panic(|| match part {
+            Part::One => self.part1(&parsed),
+            Part::Two => self.part2(&parsed),
+        });
+        let mut answers = vec![(MAIN_IMPLEMENTATION, main)];
+        for alternative in self.alternatives() {
+            if alternative.part == part {
+                answers.push((
+                    alternative.name,
+                    catch_panic(|| (alternative.solve)(&parsed)),
+                ));
+            }
+        }
+        Ok(answers)
+    }
+
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        Solution::generate(self, rng, size)
+    }
+
+    fn pictures(&self, input: &str) -> Result<Vec<Picture>> {
+        Ok(Solution::pictures(self, &self.parse(input)?))
+    }
+}
+
+pub struct Day {
+    pub number: u8,
+    pub solution: &'static dyn Runner,
+}
+
+pub static REGISTRY: &[Day] = &[
+    Day {
+        number: 1,
+        solution: &day1::Day1,
+    },
+    Day {
+        number: 2,
+        solution: &day2::Day2,
+    },
+    Day {
+        number: 3,
+        solution: &day3::Day3,
+    },
+    Day {
+        number: 4,
+        solution: &day4::Day4,
+    },
+    Day {
+        number: 5,
+        solution: &day5::Day5,
+    },
+    Day {
+        number: 6,
+        solution: &day6::Day6,
+    },
+    Day {
+        number: 7,
+        solution: &day7::Day7,
+    },
+    Day {
+        number: 8,
+        solution: &day8::Day8,
+    },
+    Day {
+        number: 9,
+        solution: &day9::Day9,
+    },
+    Day {
+        number: 10,
+        solution: &day10::Day10,
+    },
+];
+
+pub fn find(day: u8) -> Option<&'static Day> {
+    REGISTRY.iter().find(|d| d.number == day)
+}
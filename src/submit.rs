@@ -0,0 +1,242 @@
+// Submits an answer the way the puzzle site's form does: a POST of
+// `level=<part>&answer=<answer>` to /2021/day/<N>/answer. The reply is a page
+// of HTML, and the verdict is read from the sentences in it.
+//
+// Only plain HTTP is spoken, over a TcpStream, so the endpoint is either the
+// mock server (see `mock`) or a local proxy that adds TLS on the way out.
+use super::answers::Verdict;
+use super::lib::Part;
+use std::env;
+use std::fmt;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+pub const DEFAULT_ENDPOINT: &str = "http://127.0.0.1:8021";
+
+// Overrides DEFAULT_ENDPOINT when --endpoint isn't given
+pub const ENDPOINT_VARIABLE: &str = "AOC_SUBMIT_URL";
+
+// Sent as the session cookie, when set
+pub const SESSION_VARIABLE: &str = "AOC_SESSION";
+
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+pub fn endpoint(flag: Option<&str>) -> String {
+    match (flag, env::var(ENDPOINT_VARIABLE)) {
+        (Some(url), _) => url.to_string(),
+        (None, Ok(url)) => url,
+        (None, Err(_)) => DEFAULT_ENDPOINT.to_string(),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Endpoint {
+    pub host: String,
+    pub port: u16,
+    // Whatever path the url has, without a trailing slash
+    pub prefix: String,
+}
+
+impl Endpoint {
+    pub fn parse(url: &str) -> Result<Endpoint, String> {
+        let rest = url
+            .strip_prefix("http://")
+            .ok_or_else(|| format!("Expected an http:// url, got: {:?}", url))?;
+        let (authority, prefix) = match rest.find('/') {
+            Some(idx) => (&rest[..idx], rest[idx..].trim_end_matches('/')),
+            None => (rest, ""),
+        };
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => (
+                host,
+                port.parse::<u16>()
+                    .map_err(|_| format!("Expected a port number, got: {:?}", port))?,
+            ),
+            None => (authority, 80),
+        };
+        if host.is_empty() {
+            return Err(format!("Expected a host in {:?}", url));
+        }
+        Ok(Endpoint {
+            host: host.to_string(),
+            port,
+            prefix: prefix.to_string(),
+        })
+    }
+
+    // The Host header, which leaves out the port only when it's the default
+    pub fn host_header(&self) -> String {
+        match self.port {
+            80 => self.host.clone(),
+            port => format!("{}:{}", self.host, port),
+        }
+    }
+
+    pub fn path(&self, day: u8) -> String {
+        format!("{}/2021/day/{}/answer", self.prefix, day)
+    }
+}
+
+impl fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "http://{}:{}{}", self.host, self.port, self.prefix)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    Verdict(Verdict),
+    // Too soon after a wrong answer, nothing was checked. The page says how
+    // long is left, e.g. "4m 32s".
+    RateLimited { wait: Option<String> },
+}
+
+// Everything but letters, digits and -_.~ is escaped
+pub fn form_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+// The sentences the site uses for each outcome
+pub fn parse_response(response: &str) -> Result<Outcome, String> {
+    let status = response.lines().next().unwrap_or("");
+    if status.split_whitespace().nth(1) != Some("200") {
+        return Err(format!("The server replied {:?}", status));
+    }
+    if response.contains("You gave an answer too recently") {
+        let wait = response
+            .split_once("You have ")
+            .and_then(|(_, rest)| rest.split_once(" left to wait"))
+            .map(|(wait, _)| wait.to_string());
+        return Ok(Outcome::RateLimited { wait });
+    }
+    let verdict = if response.contains("That's the right answer") {
+        Verdict::Correct
+    } else if response.contains("your answer is too high") {
+        Verdict::TooHigh
+    } else if response.contains("your answer is too low") {
+        Verdict::TooLow
+    } else if response.contains("That's not the right answer") {
+        Verdict::Wrong
+    } else {
+        return Err("The reply has no verdict in it".to_string());
+    };
+    Ok(Outcome::Verdict(verdict))
+}
+
+pub fn submit(
+    endpoint: &Endpoint,
+    session: Option<&str>,
+    day: u8,
+    part: Part,
+    answer: &str,
+) -> Result<Outcome, String> {
+    let body = format!("level={}&answer={}", part.number(), form_encode(answer));
+    let mut request = format!(
+        "POST {} HTTP/1.1\r\n\
+         Host: {}\r\n\
+         Content-Type: application/x-www-form-urlencoded\r\n\
+         Content-Length: {}\r\n\
+         Connection: close\r\n",
+        endpoint.path(day),
+        endpoint.host_header(),
+        body.len()
+    );
+    if let Some(session) = session {
+        request.push_str(&format!("Cookie: session={}\r\n", session));
+    }
+    request.push_str("\r\n");
+    request.push_str(&body);
+
+    let unreachable = |e: std::io::Error| format!("Unable to reach {}: {}", endpoint, e);
+    let mut stream =
+        TcpStream::connect((endpoint.host.as_str(), endpoint.port)).map_err(unreachable)?;
+    stream
+        .set_read_timeout(Some(TIMEOUT))
+        .map_err(unreachable)?;
+    stream.write_all(request.as_bytes()).map_err(unreachable)?;
+    // The server closes the connection once it has replied
+    let mut response: Vec<u8> = Vec::new();
+    stream.read_to_end(&mut response).map_err(unreachable)?;
+    parse_response(&String::from_utf8_lossy(&response))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(text: &str) -> String {
+        format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\n\r\n<main><article><p>{}</p></article></main>",
+            text
+        )
+    }
+
+    #[test]
+    fn parses_endpoints() {
+        assert_eq!(
+            Endpoint::parse("http://localhost:8021/aoc/").unwrap(),
+            Endpoint {
+                host: "localhost".to_string(),
+                port: 8021,
+                prefix: "/aoc".to_string(),
+            }
+        );
+        let endpoint = Endpoint::parse("http://example.com").unwrap();
+        assert_eq!(endpoint.port, 80);
+        assert_eq!(endpoint.path(9), "/2021/day/9/answer");
+        assert_eq!(endpoint.host_header(), "example.com");
+        assert_eq!(
+            Endpoint::parse("http://localhost:8021")
+                .unwrap()
+                .host_header(),
+            "localhost:8021"
+        );
+        assert!(Endpoint::parse("https://example.com").is_err());
+        assert!(Endpoint::parse("http://:80").is_err());
+        assert!(Endpoint::parse("http://localhost:http").is_err());
+    }
+
+    #[test]
+    fn reads_the_verdict_from_the_page() {
+        let verdict = |text: &str| parse_response(&page(text));
+        assert_eq!(
+            verdict("That's the right answer! You are one gold star closer."),
+            Ok(Outcome::Verdict(Verdict::Correct))
+        );
+        assert_eq!(
+            verdict("That's not the right answer; your answer is too high."),
+            Ok(Outcome::Verdict(Verdict::TooHigh))
+        );
+        assert_eq!(
+            verdict("That's not the right answer; your answer is too low."),
+            Ok(Outcome::Verdict(Verdict::TooLow))
+        );
+        assert_eq!(
+            verdict("That's not the right answer."),
+            Ok(Outcome::Verdict(Verdict::Wrong))
+        );
+        assert_eq!(
+            verdict("You gave an answer too recently. You have 4m 32s left to wait."),
+            Ok(Outcome::RateLimited {
+                wait: Some("4m 32s".to_string())
+            })
+        );
+        assert!(verdict("You don't seem to be solving the right level.").is_err());
+        assert!(parse_response("HTTP/1.1 404 Not Found\r\n\r\n").is_err());
+    }
+
+    #[test]
+    fn escapes_form_values() {
+        assert_eq!(form_encode("1123524"), "1123524");
+        assert_eq!(form_encode("a b&c=d"), "a%20b%26c%3Dd");
+    }
+}
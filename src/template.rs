@@ -0,0 +1,50 @@
+use super::generate::Rng;
+use super::lib::{list, parse_all, Res, Result};
+use super::solution::Solution;
+use nom::{
+    character::complete::{newline, u32},
+    error::context,
+};
+
+fn parse_line(input: &str) -> Res<&str, u32> {
+    context("line", u32)(input)
+}
+
+// The sample starts out empty, and has nothing to parse until it's pasted in
+pub fn parse_lines(input: &str) -> Res<&str, Vec<u32>> {
+    if input.trim().is_empty() {
+        return Ok((input, Vec::new()));
+    }
+    list(newline, parse_line)(input)
+}
+
+fn generate(rng: &mut Rng, size: usize) -> String {
+    (0..size.max(1))
+        .map(|_| format!("{}\n", rng.below(1000)))
+        .collect()
+}
+
+pub struct Day0;
+
+impl Solution for Day0 {
+    type Input = Vec<u32>;
+
+    fn parse(&self, input: &str) -> Result<Vec<u32>> {
+        parse_all(input, parse_lines)
+    }
+
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        Some(generate(rng, size))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_the_sample() {
+        let sample = include_str!("../inputs/day0.test");
+        assert!(parse_all(sample, parse_lines).is_ok());
+    }
+}
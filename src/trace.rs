@@ -0,0 +1,225 @@
+// Solvers explain themselves by emitting trace events: a name, a few fields
+// and a sentence for `run --explain` to print. Events are only built while a
+// run is being recorded, so emitting one is almost free the rest of the time.
+//
+//     trace::emit(|| {
+//         Event::new("win", format!("Board {} wins on {}", board, draw))
+//             .with("board", board)
+//             .with("draw", draw)
+//     });
+//
+// Recording is per thread, so days running in parallel keep their own events.
+//
+// A grid or a list in an event is a snapshot of the solver's state, which is
+// what `animate` draws.
+use std::cell::RefCell;
+use std::fmt;
+
+// Rows of cells, where None is a cell with nothing in it yet
+pub type Grid = Vec<Vec<Option<i64>>>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Number(i64),
+    Text(String),
+    Grid(Grid),
+    List(Vec<i64>),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Number(n) => write!(f, "{}", n),
+            Value::Text(s) => write!(f, "{}", s),
+            Value::Grid(grid) => write!(
+                f,
+                "{}x{} grid",
+                grid.len(),
+                grid.first().map_or(0, Vec::len)
+            ),
+            Value::List(items) => write!(f, "{:?}", items),
+        }
+    }
+}
+
+impl From<i64> for Value {
+    fn from(n: i64) -> Self {
+        Value::Number(n)
+    }
+}
+
+impl From<u32> for Value {
+    fn from(n: u32) -> Self {
+        Value::Number(n as i64)
+    }
+}
+
+impl From<u16> for Value {
+    fn from(n: u16) -> Self {
+        Value::Number(n as i64)
+    }
+}
+
+impl From<usize> for Value {
+    fn from(n: usize) -> Self {
+        Value::Number(n as i64)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Self {
+        Value::Text(s.to_string())
+    }
+}
+
+impl From<Grid> for Value {
+    fn from(grid: Grid) -> Self {
+        Value::Grid(grid)
+    }
+}
+
+impl From<Vec<i64>> for Value {
+    fn from(items: Vec<i64>) -> Self {
+        Value::List(items)
+    }
+}
+
+// Lays grids out side by side, in rows of about as many as there are rows, with
+// an empty cell between them. Every grid takes the space of the largest one.
+pub fn tile(grids: &[Grid]) -> Grid {
+    let height = grids.iter().map(Vec::len).max().unwrap_or(0);
+    let width = grids.iter().flatten().map(Vec::len).max().unwrap_or(0);
+    let per_row = (1..).find(|n| n * n >= grids.len()).unwrap_or(1).max(1);
+    let mut tiled: Grid = Vec::new();
+    for (row_idx, row) in grids.chunks(per_row).enumerate() {
+        if row_idx > 0 {
+            tiled.push(Vec::new());
+        }
+        for line in 0..height {
+            let mut cells: Vec<Option<i64>> = Vec::new();
+            for (idx, grid) in row.iter().enumerate() {
+                if idx > 0 {
+                    cells.push(None);
+                }
+                let mut part: Vec<Option<i64>> = grid.get(line).cloned().unwrap_or_default();
+                part.resize(width, None);
+                cells.extend(part);
+            }
+            tiled.push(cells);
+        }
+    }
+    tiled
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Event {
+    pub name: &'static str,
+    pub fields: Vec<(&'static str, Value)>,
+    pub message: String,
+}
+
+impl Event {
+    pub fn new(name: &'static str, message: String) -> Event {
+        Event {
+            name,
+            fields: Vec::new(),
+            message,
+        }
+    }
+
+    pub fn with(mut self, key: &'static str, value: impl Into<Value>) -> Event {
+        self.fields.push((key, value.into()));
+        self
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.fields.iter().find(|(k, _)| *k == key).map(|(_, v)| v)
+    }
+
+    pub fn number(&self, key: &str) -> Option<i64> {
+        match self.get(key)? {
+            Value::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+}
+
+thread_local! {
+    static RECORDING: RefCell<Option<Vec<Event>>> = const { RefCell::new(None) };
+}
+
+pub fn emit(event: impl FnOnce() -> Event) {
+    RECORDING.with(|recording| {
+        if let Some(events) = recording.borrow_mut().as_mut() {
+            events.push(event());
+        }
+    });
+}
+
+// Runs f and returns the events it emitted. A recording inside another one
+// keeps its events to itself.
+pub fn record<R>(f: impl FnOnce() -> R) -> (R, Vec<Event>) {
+    let outer = RECORDING.with(|recording| recording.replace(Some(Vec::new())));
+    let result = f();
+    let events = RECORDING.with(|recording| recording.replace(outer));
+    (result, events.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(n: usize) {
+        emit(|| Event::new("step", format!("Step {}", n)).with("n", n));
+    }
+
+    #[test]
+    fn only_records_inside_a_recording() {
+        step(1);
+        let ((), events) = record(|| {
+            step(2);
+            step(3);
+        });
+        step(4);
+        let steps: Vec<Option<i64>> = events.iter().map(|e| e.number("n")).collect();
+        assert_eq!(steps, vec![Some(2), Some(3)]);
+        assert_eq!(events[0].message, "Step 2");
+        assert_eq!(events[0].get("missing"), None);
+    }
+
+    #[test]
+    fn nested_recordings_are_separate() {
+        let (inner, outer) = record(|| {
+            step(1);
+            let ((), inner) = record(|| step(2));
+            step(3);
+            inner
+        });
+        assert_eq!(inner.len(), 1);
+        assert_eq!(outer.len(), 2);
+    }
+
+    #[test]
+    fn tiles_grids_in_a_square() {
+        let grid = |n: i64| vec![vec![Some(n); 2]; 2];
+        let tiled = tile(&[grid(1), grid(2), grid(3)]);
+        let row = |cells: &[Option<i64>]| cells.to_vec();
+        assert_eq!(
+            tiled,
+            vec![
+                row(&[Some(1), Some(1), None, Some(2), Some(2)]),
+                row(&[Some(1), Some(1), None, Some(2), Some(2)]),
+                Vec::new(),
+                row(&[Some(3), Some(3)]),
+                row(&[Some(3), Some(3)]),
+            ]
+        );
+        assert_eq!(Value::from(tiled).to_string(), "5x5 grid");
+        assert!(tile(&[]).is_empty());
+    }
+
+    #[test]
+    fn events_are_not_built_unless_recorded() {
+        emit(|| panic!("Built an event outside a recording"));
+    }
+}
@@ -0,0 +1,91 @@
+// Watch mode re-solves a day whenever its input or sample changes. There are no
+// file change notifications in std, so each file's modification time and length
+// are polled instead.
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+pub const DEFAULT_INTERVAL: Duration = Duration::from_millis(500);
+
+// Some filesystems only keep the modification time to the second, so the
+// length is compared too
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stamp {
+    modified: Option<SystemTime>,
+    len: u64,
+}
+
+// None when the file doesn't exist (yet)
+pub fn stamp(path: &Path) -> Option<Stamp> {
+    let metadata = fs::metadata(path).ok()?;
+    Some(Stamp {
+        modified: metadata.modified().ok(),
+        len: metadata.len(),
+    })
+}
+
+pub struct Watcher {
+    files: Vec<(PathBuf, Option<Stamp>)>,
+}
+
+impl Watcher {
+    // Nothing has been seen yet, so the first poll returns every file that exists
+    pub fn new(paths: Vec<PathBuf>) -> Watcher {
+        Watcher {
+            files: paths.into_iter().map(|path| (path, None)).collect(),
+        }
+    }
+
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.files.iter().map(|(path, _)| path.as_path())
+    }
+
+    // The files that appeared or changed since the last poll. A file that was
+    // removed isn't returned, there's nothing to solve.
+    pub fn poll(&mut self) -> Vec<PathBuf> {
+        let mut changed: Vec<PathBuf> = Vec::new();
+        for (path, last) in self.files.iter_mut() {
+            let current = stamp(path);
+            if current != *last {
+                *last = current;
+                if current.is_some() {
+                    changed.push(path.clone());
+                }
+            }
+        }
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::process;
+
+    #[test]
+    fn reports_files_that_appear_or_change() {
+        let dir = env::temp_dir().join(format!("aoc-2021-watch-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("day1");
+        let sample = dir.join("day1.test");
+        fs::write(&input, "199\n").unwrap();
+
+        let mut watcher = Watcher::new(vec![input.clone(), sample.clone()]);
+        assert_eq!(watcher.poll(), vec![input.clone()]);
+        assert!(watcher.poll().is_empty());
+
+        fs::write(&sample, "199\n200\n").unwrap();
+        assert_eq!(watcher.poll(), vec![sample.clone()]);
+        fs::write(&input, "199\n200\n208\n").unwrap();
+        assert_eq!(watcher.poll(), vec![input.clone()]);
+
+        // Removing a file isn't a change to solve, but bringing it back is
+        fs::remove_file(&sample).unwrap();
+        assert!(watcher.poll().is_empty());
+        fs::write(&sample, "199\n").unwrap();
+        assert_eq!(watcher.poll(), vec![sample.clone()]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
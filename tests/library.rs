@@ -0,0 +1,57 @@
+// Uses the library the way another tool would, without going through the binary
+use aoc_2021::lib::{parse_all, Monoid, Part, Semigroup};
+use aoc_2021::solution::{self, Answer};
+use aoc_2021::{day2, day9};
+
+fn sample(day: u8) -> String {
+    let path = format!("{}/inputs/day{}.test", env!("CARGO_MANIFEST_DIR"), day);
+    std::fs::read_to_string(path).unwrap()
+}
+
+#[test]
+fn calls_a_day_directly() {
+    let rows = parse_all(&sample(9), day9::parse_lines).unwrap();
+    let matrix = day9::build_matrix(rows).unwrap();
+    assert_eq!(day9::part1(&matrix), 15);
+    assert_eq!(day9::basin_sizes(&matrix), vec![14, 9, 9, 3]);
+
+    let (_, aim) = day2::aim_parser("forward 5").unwrap();
+    assert_eq!((aim.horizontal, aim.aim), (5, 0));
+}
+
+#[test]
+fn runs_a_day_from_the_registry() {
+    let day = solution::find(2).unwrap();
+    let run = day.solution.run(&sample(2), &[Part::One]).unwrap();
+    assert_eq!(
+        run.parts[0].answer.as_ref().ok(),
+        Some(&Answer::Number(150))
+    );
+}
+
+#[derive(Debug, Semigroup, Monoid)]
+#[monoid(crate = "aoc_2021")]
+struct Tally {
+    position: day2::Position,
+    #[monoid(sum)]
+    moves: u32,
+}
+
+#[test]
+fn derives_instances_outside_the_crate() {
+    let (_, forward) = day2::position_parser("forward 5").unwrap();
+    let tally = Tally::mappend(
+        Tally::mempty(),
+        Tally {
+            position: forward,
+            moves: 1,
+        },
+    );
+    assert_eq!((tally.position.horizontal, tally.moves), (5, 1));
+}
+
+#[test]
+fn answers_keep_every_digit() {
+    assert_eq!(Answer::from(usize::MAX).to_string(), usize::MAX.to_string());
+    assert_eq!(Answer::from(i64::MIN).to_string(), i64::MIN.to_string());
+}
@@ -0,0 +1,151 @@
+// Solves every inputs/day<N>.test sample with the binary and compares the
+// output against the example answers from the puzzle descriptions
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+// None where the part isn't implemented
+const EXPECTED: &[(u8, Option<&str>, Option<&str>)] = &[
+    (1, Some("7"), Some("5")),
+    (2, Some("150"), Some("900")),
+    (3, Some("198"), Some("230")),
+    (4, Some("4512"), Some("1924")),
+    (5, Some("5"), Some("12")),
+    (6, None, Some("26984457539")),
+    (7, Some("37"), Some("168")),
+    (8, Some("26"), None),
+    (9, Some("15"), Some("1134")),
+    (10, Some("26397"), Some("288957")),
+];
+
+fn sample_path(day: u8) -> String {
+    format!("{}/inputs/day{}.test", env!("CARGO_MANIFEST_DIR"), day)
+}
+
+fn run_sample(day: u8) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_aoc-2021"))
+        .args([
+            "run",
+            "--day",
+            &day.to_string(),
+            "--input",
+            &sample_path(day),
+        ])
+        .output()
+        .expect("Unable to run aoc-2021");
+    assert!(
+        output.status.success(),
+        "Day {} failed: {}",
+        day,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn expected_line(day: u8, part: u8, answer: Option<&str>) -> String {
+    format!(
+        "Day {}, Part {}: {}",
+        day,
+        part,
+        answer.unwrap_or("not implemented")
+    )
+}
+
+#[test]
+fn every_sample_has_an_expected_answer() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("inputs");
+    for entry in fs::read_dir(dir).unwrap() {
+        let entry = entry.unwrap();
+        // A new day's sample is empty until it's pasted in, and so is day0's,
+        // which only the scaffold's stub reads
+        if entry.metadata().unwrap().len() == 0 {
+            continue;
+        }
+        let name = entry.file_name().into_string().unwrap();
+        if let Some(day) = name
+            .strip_prefix("day")
+            .and_then(|rest| rest.strip_suffix(".test"))
+        {
+            let day: u8 = day.parse().unwrap();
+            assert!(
+                EXPECTED.iter().any(|(d, _, _)| *d == day),
+                "No expected answers for {}",
+                name
+            );
+        }
+    }
+}
+
+#[test]
+fn samples_produce_the_published_answers() {
+    for (day, part1, part2) in EXPECTED {
+        let output = run_sample(*day);
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(
+            lines,
+            vec![
+                expected_line(*day, 1, *part1),
+                expected_line(*day, 2, *part2)
+            ],
+            "Day {}",
+            day
+        );
+    }
+}
+
+#[test]
+fn bad_input_fails_with_a_parse_error() {
+    let output = Command::new(env!("CARGO_BIN_EXE_aoc-2021"))
+        .args(["run", "--day", "2", "--input", &sample_path(3)])
+        .output()
+        .expect("Unable to run aoc-2021");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success());
+    assert!(
+        stderr.contains("in movement: expected forward|up|down"),
+        "{}",
+        stderr
+    );
+    assert!(stderr.contains("day3.test:1:1"), "{}", stderr);
+}
+
+#[test]
+fn json_output_has_a_record_per_part() {
+    let output = Command::new(env!("CARGO_BIN_EXE_aoc-2021"))
+        .args([
+            "run",
+            "--day",
+            "6",
+            "--input",
+            &sample_path(6),
+            "--format",
+            "json",
+        ])
+        .output()
+        .expect("Unable to run aoc-2021");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("{\"day\":6,\"part\":1,\"status\":\"not_implemented\""));
+    assert!(lines[1].starts_with("{\"day\":6,\"part\":2,\"status\":\"ok\",\"answer\":26984457539,"));
+    assert!(lines[1].ends_with(&format!("\"input\":\"{}\"}}", sample_path(6))));
+}
+
+#[test]
+fn explain_narrates_before_each_answer() {
+    let output = Command::new(env!("CARGO_BIN_EXE_aoc-2021"))
+        .args(["run", "--day", "3", "--part", "2", "--explain"])
+        .args(["--input", &sample_path(3)])
+        .output()
+        .expect("Unable to run aoc-2021");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(
+        lines.first(),
+        Some(&"  Whittling 12 numbers down to the oxygen generator rating")
+    );
+    assert!(lines.contains(&"  Bit 3: 1 zeros and 1 ones, keeping the 0s leaves 1"));
+    assert_eq!(lines.last(), Some(&"Day 3, Part 2: 230"));
+}
@@ -0,0 +1,73 @@
+// Submits answers with the binary to the bundled mock server, which checks them
+// against answers.txt, and follows them into a fresh ledger
+use std::env;
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::process::{Child, Command, Output, Stdio};
+
+struct MockServer(Child);
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+// Starts the mock server on a free port, and returns its url
+fn start_mock_server() -> (MockServer, String) {
+    let answers = format!("{}/answers.txt", env!("CARGO_MANIFEST_DIR"));
+    let mut child = Command::new(env!("CARGO_BIN_EXE_aoc-2021"))
+        .args(["mock-server", "--port", "0", "--cooldown", "0"])
+        .args(["--answers", &answers])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("Unable to start the mock server");
+    let mut line = String::new();
+    BufReader::new(child.stdout.take().unwrap())
+        .read_line(&mut line)
+        .unwrap();
+    let url = line
+        .trim()
+        .strip_prefix("Listening on ")
+        .unwrap_or_else(|| panic!("Unexpected first line: {:?}", line))
+        .to_string();
+    (MockServer(child), url)
+}
+
+fn submit(url: &str, ledger: &str, answer: &str) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_aoc-2021"))
+        .args(["submit", "--day", "9", "--part", "2", "--answer", answer])
+        .args(["--endpoint", url, "--answers", ledger])
+        .output()
+        .expect("Unable to run aoc-2021")
+}
+
+#[test]
+fn records_each_verdict_in_the_ledger() {
+    let (_server, url) = start_mock_server();
+    let ledger = env::temp_dir().join(format!("aoc-2021-submit-{}.txt", std::process::id()));
+    let ledger_arg = ledger.to_str().unwrap();
+    let _ = fs::remove_file(&ledger);
+
+    let output = submit(&url, ledger_arg, "54756");
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("54756 is too-low"), "{}", stdout);
+
+    // The ledger already rules this one out, so it isn't sent
+    let output = submit(&url, ledger_arg, "1000");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success());
+    assert!(stderr.contains("Not submitting"), "{}", stderr);
+
+    let output = submit(&url, ledger_arg, "1123524");
+    assert!(output.status.success());
+
+    assert_eq!(
+        fs::read_to_string(&ledger).unwrap(),
+        "9 2 too-low 54756\n9 2 correct 1123524\n"
+    );
+    fs::remove_file(&ledger).unwrap();
+}